@@ -0,0 +1,18 @@
+//! Render headless: mismos argumentos que `Lab5 --headless`, sin dependencias de ventana.
+
+use lab5::offline;
+use lab5::utils::cli::{CliArgs, print_usage};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    match CliArgs::parse(std::env::args().skip(1))? {
+        Some(args) => offline::run(&args),
+        None => { print_usage(); Ok(()) }
+    }
+}
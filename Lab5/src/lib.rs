@@ -0,0 +1,17 @@
+//! Rasterizador por software con shaders procedurales escritos en Rust.
+//!
+//! - [`math`]: vectores, matrices y ruido.
+//! - [`renderer`]: framebuffer, uniforms y pipeline (`draw_mesh` + trait [`renderer::pipeline::Shader`]).
+//! - [`scene`]: mallas `.obj`, cámara e input.
+//! - [`shaders`]: estrella, planetas, anillos y luna (`make_shader`).
+//! - [`offline`]: render sin ventana a PNG.
+//!
+//! El viewer interactivo (`winit` + `pixels`) vive en el binario `Lab5`
+//! detrás de la feature `viewer`; la librería no depende de ventanas.
+
+pub mod math;
+pub mod renderer;
+pub mod scene;
+pub mod shaders;
+pub mod utils;
+pub mod offline;
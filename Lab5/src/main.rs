@@ -1,16 +1,11 @@
-mod math;
-mod scene;
-mod renderer;
-mod shaders;
-mod utils;
-
 use std::time::Instant;
 
-use math::{Vec3, viewport};
-use renderer::{Framebuffer, Uniforms, PlanetParams, uniforms::StarParams};
-use renderer::pipeline::draw_mesh;
-use scene::{load_obj, Camera, Input, Action};
-use shaders::noise::NoiseType;
+use lab5::offline;
+use lab5::renderer::{Bloom, Framebuffer, ShadowMap};
+use lab5::scene::{Camera, Input, Action, NodeId, Scene};
+use lab5::shaders::{ShaderKind, ShaderLibrary, noise::NoiseType};
+use lab5::shaders::blackbody::{MAX_KELVIN, MIN_KELVIN};
+use lab5::utils::cli::{CliArgs, print_usage};
 
 use pixels::{Pixels, SurfaceTexture};
 use winit::{
@@ -20,10 +15,28 @@ use winit::{
     window::WindowBuilder,
 };
 
-fn main() -> Result<(), String> {
+fn main() {
+    // Errores (p.ej. de la escena) legibles, no en formato Debug
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let args = match CliArgs::parse(std::env::args().skip(1))? {
+        Some(a) => a,
+        None => { print_usage(); return Ok(()); }
+    };
+
+    // Render sin ventana
+    if args.headless {
+        return offline::run(&args);
+    }
+
     // Ventana 
-    let width: u32 = 960;
-    let height: u32 = 540;
+    let width: u32 = args.width;
+    let height: u32 = args.height;
 
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
@@ -37,19 +50,14 @@ fn main() -> Result<(), String> {
         .map_err(|e| format!("Pixels: {e}"))?;
 
     // Framebuffer
-    let mut fb = Framebuffer::new(width as usize, height as usize);
-
-    // Carga esfera
-    println!("Cargando esfera...");
-    let mesh = load_obj("assets/sphere.obj")?;
-    println!("OK Esfera cargada: {} vertices, {} triangulos", 
-             mesh.vertices.len(), mesh.indices.len());
-
-    // Camara 
-    let mut cam = Camera::default();
-    cam.eye = Vec3::new(0.0, 0.0, 3.5); 
-    cam.center = Vec3::new(0.0, 0.0, 0.0);
-    cam.set_aspect(width as f32 / height as f32);
+    let mut fb = Framebuffer::with_msaa(width as usize, height as usize, args.msaa);
+    let mut shadows = ShadowMap::new(args.shadow_map);
+
+    // Escena (carga la esfera) y camara
+    println!("Cargando escena...");
+    let (mut scene, mut cam) = offline::build_scene(&args)?;
+    println!("OK Escena cargada: {} nodos, {} vertices por malla, {} triangulos",
+             scene.nodes.len(), scene.meshes[0].vertices.len(), scene.meshes[0].indices.len());
     
     println!("Camara: eye={:?}, center={:?}", cam.eye, cam.center);
 
@@ -57,23 +65,20 @@ fn main() -> Result<(), String> {
     let mut input = Input::new();
 
     // Uniforms base
-    let mut uniforms = Uniforms {
-        time: 0.0,
-        light_dir: Vec3::new(0.5, 0.7, 0.2).normalize(),
-        view: cam.view(),
-        proj: cam.proj(),
-        model: math::Mat4::identity(),
-        camera_pos: cam.eye,
-        planet: PlanetParams::default(),
-        star: StarParams::default(),
-    };
+    let mut uniforms = offline::base_uniforms(&cam);
+
+    // Shaders compartidos por los nodos; los ajustes de teclado van a la estrella
+    let shaders = ShaderLibrary::new();
 
-    // Shader de estrella
-    let mut star_shader = crate::shaders::star::Star::default();
+    // Cuerpo seleccionado (destino de Shader1..7 y de anillos/luna/atmósfera/nubes) y pausa
+    let mut selected = scene.bodies()[0];
+    let mut paused = false;
+    window.set_title(&title(&scene, selected, paused));
+    let mut tonemap = args.tonemap;
+    let mut bloom = Bloom { strength: args.bloom, ..Bloom::default() };
 
     let mut running = true;
     let mut last = Instant::now();
-    let mut frame_count = 0;
     let mut saved_screenshot = false;
 
     print_controls();
@@ -128,6 +133,26 @@ fn main() -> Result<(), String> {
                         VirtualKeyCode::Key3 => Some(Action::NoiseCellular),
                         VirtualKeyCode::Key4 => Some(Action::ToggleCellularFlares),
 
+                        // Cuerpos: shader (F1..F7), anillos (R), luna (M), atmósfera (G), nubes (U), pausa (F), selección (Tab)
+                        VirtualKeyCode::F1 => Some(Action::Shader1),
+                        VirtualKeyCode::F2 => Some(Action::Shader2),
+                        VirtualKeyCode::F3 => Some(Action::Shader3),
+                        VirtualKeyCode::F4 => Some(Action::Shader4),
+                        VirtualKeyCode::F5 => Some(Action::Shader5),
+                        VirtualKeyCode::F6 => Some(Action::Shader6),
+                        VirtualKeyCode::F7 => Some(Action::Shader7),
+                        VirtualKeyCode::R => Some(Action::ToggleRings),
+                        VirtualKeyCode::M => Some(Action::ToggleMoon),
+                        VirtualKeyCode::G => Some(Action::ToggleAtmosphere),
+                        VirtualKeyCode::U => Some(Action::ToggleClouds),
+                        VirtualKeyCode::F => Some(Action::PauseRotation),
+                        VirtualKeyCode::Tab => Some(Action::NextBody),
+
+                        // Tonemapping (T) y bloom (K/L)
+                        VirtualKeyCode::T => Some(Action::CycleTonemap),
+                        VirtualKeyCode::K => Some(Action::BloomDec),
+                        VirtualKeyCode::L => Some(Action::BloomInc),
+
                         // Utilidad
                         VirtualKeyCode::P => Some(Action::Screenshot),
                         VirtualKeyCode::H => { if is_down { print_controls(); } None },
@@ -167,72 +192,133 @@ fn main() -> Result<(), String> {
                     return; 
                 }
 
-                // Actualizar parametros de la estrella
-                let step = dt * 0.5;
-                
-                // Temperatura
-                if input.is_held(Action::TempInc) { 
-                    uniforms.star.temp_norm = (uniforms.star.temp_norm + step).min(1.0);
-                    println!("Temperatura: {:.2}", uniforms.star.temp_norm);
+                // Cuerpo seleccionado: shader, anillos, luna, atmósfera, nubes y pausa
+                let mut changed = false;
+                if input.is_pressed(Action::NextBody) {
+                    let bodies = scene.bodies();
+                    let i = bodies.iter().position(|&b| b == selected).map_or(0, |i| (i + 1) % bodies.len());
+                    selected = bodies[i];
+                    changed = true;
                 }
-                if input.is_held(Action::TempDec) { 
-                    uniforms.star.temp_norm = (uniforms.star.temp_norm - step).max(0.0);
-                    println!("Temperatura: {:.2}", uniforms.star.temp_norm);
+                for (action, kind) in SHADER_ACTIONS.iter().zip(ShaderKind::BODIES) {
+                    if input.is_pressed(*action) {
+                        scene.set_shader(selected, kind);
+                        changed = true;
+                    }
                 }
-                
-                // Flares
-                if input.is_held(Action::FlareInc) { 
-                    uniforms.star.flare_intensity = (uniforms.star.flare_intensity + step).min(2.0);
-                    println!("Flare Intensity: {:.2}", uniforms.star.flare_intensity);
+                if input.is_pressed(Action::ToggleRings) {
+                    scene.toggle_child(selected, ShaderKind::Rings);
+                    changed = true;
                 }
-                if input.is_held(Action::FlareDec) { 
-                    uniforms.star.flare_intensity = (uniforms.star.flare_intensity - step).max(0.0);
-                    println!("Flare Intensity: {:.2}", uniforms.star.flare_intensity);
+                if input.is_pressed(Action::ToggleMoon) {
+                    scene.toggle_child(selected, ShaderKind::Moon);
+                    changed = true;
                 }
-                
-                // Noise Scale
-                if input.is_held(Action::NoiseScaleInc) { 
-                    uniforms.star.noise_scale = (uniforms.star.noise_scale + step).min(5.0);
-                    println!("Noise Scale: {:.2}", uniforms.star.noise_scale);
+                if input.is_pressed(Action::ToggleAtmosphere) {
+                    scene.toggle_child(selected, ShaderKind::Atmosphere);
+                    changed = true;
                 }
-                if input.is_held(Action::NoiseScaleDec) { 
-                    uniforms.star.noise_scale = (uniforms.star.noise_scale - step).max(0.1);
-                    println!("Noise Scale: {:.2}", uniforms.star.noise_scale);
+                if input.is_pressed(Action::ToggleClouds) {
+                    scene.toggle_child(selected, ShaderKind::Clouds);
+                    changed = true;
                 }
-                
-                // Rotation Speed
-                if input.is_held(Action::RotSpeedInc) { 
-                    uniforms.star.rot_speed = (uniforms.star.rot_speed + step*0.5).min(2.0);
-                    println!("Rotation Speed: {:.2}", uniforms.star.rot_speed);
+                if input.is_pressed(Action::PauseRotation) {
+                    paused = !paused;
+                    changed = true;
                 }
-                if input.is_held(Action::RotSpeedDec) { 
-                    uniforms.star.rot_speed = (uniforms.star.rot_speed - step*0.5).max(0.0);
-                    println!("Rotation Speed: {:.2}", uniforms.star.rot_speed);
+                if changed {
+                    let t = title(&scene, selected, paused);
+                    println!("{}", t);
+                    window.set_title(&t);
                 }
+
+                // Actualizar parametros de la estrella
+                let step = dt * 0.5;
+                if let Some(i) = scene.find_kind(ShaderKind::Star) {
+                    let star = &mut scene.nodes[i].star;
+
+                    // Temperatura (escala logarítmica: ~2x por segundo)
+                    if input.is_held(Action::TempInc) { 
+                        star.temperature = (star.temperature * (1.0 + step*1.4)).min(MAX_KELVIN);
+                        println!("Temperatura: {:.0} K", star.temperature);
+                    }
+                    if input.is_held(Action::TempDec) { 
+                        star.temperature = (star.temperature / (1.0 + step*1.4)).max(MIN_KELVIN);
+                        println!("Temperatura: {:.0} K", star.temperature);
+                    }
                 
-                // Tipos de ruido
-                if input.is_pressed(Action::NoisePerlin) {
-                    uniforms.star.noise_type = NoiseType::Perlin;
-                    println!("Noise Type: PERLIN");
+                    // Flares
+                    if input.is_held(Action::FlareInc) { 
+                        star.flare_intensity = (star.flare_intensity + step).min(2.0);
+                        println!("Flare Intensity: {:.2}", star.flare_intensity);
+                    }
+                    if input.is_held(Action::FlareDec) { 
+                        star.flare_intensity = (star.flare_intensity - step).max(0.0);
+                        println!("Flare Intensity: {:.2}", star.flare_intensity);
+                    }
+                
+                    // Noise Scale
+                    if input.is_held(Action::NoiseScaleInc) { 
+                        star.noise_scale = (star.noise_scale + step).min(5.0);
+                        println!("Noise Scale: {:.2}", star.noise_scale);
+                    }
+                    if input.is_held(Action::NoiseScaleDec) { 
+                        star.noise_scale = (star.noise_scale - step).max(0.1);
+                        println!("Noise Scale: {:.2}", star.noise_scale);
+                    }
+                
+                    // Rotation Speed
+                    if input.is_held(Action::RotSpeedInc) { 
+                        star.rot_speed = (star.rot_speed + step*0.5).min(2.0);
+                        println!("Rotation Speed: {:.2}", star.rot_speed);
+                    }
+                    if input.is_held(Action::RotSpeedDec) { 
+                        star.rot_speed = (star.rot_speed - step*0.5).max(0.0);
+                        println!("Rotation Speed: {:.2}", star.rot_speed);
+                    }
+                
+                    // Tipos de ruido
+                    if input.is_pressed(Action::NoisePerlin) {
+                        star.noise_type = NoiseType::Perlin;
+                        println!("Noise Type: PERLIN");
+                    }
+                    if input.is_pressed(Action::NoiseSimplex) {
+                        star.noise_type = NoiseType::Simplex;
+                        println!("Noise Type: SIMPLEX");
+                    }
+                    if input.is_pressed(Action::NoiseCellular) {
+                        star.noise_type = NoiseType::Cellular;
+                        println!("Noise Type: CELLULAR");
+                    }
+                    if input.is_pressed(Action::ToggleCellularFlares) {
+                        star.use_cellular_flares = !star.use_cellular_flares;
+                        println!("Cellular Flares: {}", if star.use_cellular_flares { "ON" } else { "OFF" });
+                    }
                 }
-                if input.is_pressed(Action::NoiseSimplex) {
-                    uniforms.star.noise_type = NoiseType::Simplex;
-                    println!("Noise Type: SIMPLEX");
+
+                // Bloom
+                if input.is_held(Action::BloomInc) {
+                    bloom.strength = (bloom.strength + step).min(3.0);
+                    println!("Bloom: {:.2}", bloom.strength);
                 }
-                if input.is_pressed(Action::NoiseCellular) {
-                    uniforms.star.noise_type = NoiseType::Cellular;
-                    println!("Noise Type: CELLULAR");
+                if input.is_held(Action::BloomDec) {
+                    bloom.strength = (bloom.strength - step).max(0.0);
+                    println!("Bloom: {:.2}", bloom.strength);
                 }
-                if input.is_pressed(Action::ToggleCellularFlares) {
-                    uniforms.star.use_cellular_flares = !uniforms.star.use_cellular_flares;
-                    println!("Cellular Flares: {}", if uniforms.star.use_cellular_flares { "ON" } else { "OFF" });
+
+                if input.is_pressed(Action::CycleTonemap) {
+                    tonemap.operator = tonemap.operator.next();
+                    println!("Tonemap: {}", tonemap.operator.name().to_uppercase());
                 }
 
                 // Actualizar camara
                 update_camera(&mut cam, &input, dt);
 
                 // Actualizar uniforms
-                uniforms.time += dt;
+                // En pausa se congela el tiempo: rotación propia, órbitas y animación
+                if !paused {
+                    uniforms.time += dt;
+                }
                 uniforms.view = cam.view();
                 uniforms.proj = cam.proj();
                 uniforms.camera_pos = cam.eye;
@@ -242,29 +328,18 @@ fn main() -> Result<(), String> {
 
             // Render 
             Event::RedrawRequested(_) => {
-                frame_count += 1;
-
                 let size = window.inner_size();
                 let fw = size.width as usize;
                 let fh = size.height as usize;
 
                 // Resize framebuffer si es necesario
                 if fb.width != fw || fb.height != fh {
-                    fb = Framebuffer::new(fw, fh);
+                    fb = Framebuffer::with_msaa(fw, fh, args.msaa);
                     cam.set_aspect(fw as f32 / fh as f32);
                 }
 
-                let vp = viewport(0.0, 0.0, fw as f32, fh as f32, 1.0);
-
-                // Limpiar buffers con fondo negro espacial
-                fb.clear_color(renderer::buffers::Color::rgb(2, 2, 5));
-                fb.clear_depth();
-
-                // Renderizar estrella centrada con escala apropiada
-                let mut u_star = uniforms;
-                u_star.model = math::mat::scale(Vec3::new(1.2, 1.2, 1.2));
-                
-                draw_mesh(&mut fb, &mesh, &mut star_shader, &u_star, vp);
+                // Renderizar todos los cuerpos de la escena
+                offline::render_frame(&mut fb, &scene, &shaders, &uniforms, &mut shadows, &[&bloom], &tonemap);
 
                 // Captura de pantalla
                 if input.is_pressed(Action::Screenshot) {
@@ -289,14 +364,8 @@ fn main() -> Result<(), String> {
 
                 // Copiar framebuffer a pixels
                 let frame = pixels.frame_mut();
-                let px_count = (frame.len() / 4).min(fb.color.len());
-                for i in 0..px_count {
-                    let c = fb.color[i];
-                    let o = i * 4;
-                    frame[o + 0] = c.r;
-                    frame[o + 1] = c.g;
-                    frame[o + 2] = c.b;
-                    frame[o + 3] = c.a;
+                for (px, c) in frame.chunks_exact_mut(4).zip(fb.color.iter()) {
+                    px.copy_from_slice(&c.to_rgba());
                 }
 
                 if let Err(e) = pixels.render() {
@@ -320,6 +389,13 @@ fn print_controls() {
     println!("    Space/Shift   - Subir/Bajar");
     println!("    Flechas       - Rotar camara");
     println!("-------------------------------------------------------------");
+    println!("  CUERPOS:");
+    println!("    Tab    - Seleccionar siguiente cuerpo");
+    println!("    F1..F7 - Shader: rocky, gas, scifi, lava, ice, star, ocean");
+    println!("    R / M  - Anillos / luna del cuerpo seleccionado");
+    println!("    G / U  - Atmosfera / nubes del cuerpo seleccionado");
+    println!("    F      - Pausar rotacion");
+    println!("-------------------------------------------------------------");
     println!("  AJUSTES DE ESTRELLA:");
     println!("    Q/E - Temperatura (color)");
     println!("    Z/X - Intensidad de flares");
@@ -332,6 +408,10 @@ fn print_controls() {
     println!("    3 - Cellular Noise");
     println!("    4 - Toggle Cellular para Flares");
     println!("-------------------------------------------------------------");
+    println!("  IMAGEN:");
+    println!("    T   - Alternar tonemapping (Exposure/Reinhard/ACES)");
+    println!("    K/L - Intensidad de bloom");
+    println!("-------------------------------------------------------------");
     println!("  UTILIDAD:");
     println!("    P   - Captura de pantalla");
     println!("    H   - Mostrar esta ayuda");
@@ -340,6 +420,24 @@ fn print_controls() {
 }
 
 // Helpers 
+const SHADER_ACTIONS: [Action; 7] = [
+    Action::Shader1, Action::Shader2, Action::Shader3, Action::Shader4, Action::Shader5, Action::Shader6,
+    Action::Shader7,
+];
+
+/// Estado visible en el título de la ventana y en consola
+fn title(scene: &Scene, selected: NodeId, paused: bool) -> String {
+    let n = &scene.nodes[selected];
+    let on_off = |kind| match scene.child_of_kind(selected, kind) {
+        Some(c) if scene.nodes[c].visible => "ON",
+        _ => "OFF",
+    };
+    format!("Lab 05 - {} [{}] | anillos {} | luna {} | atmosfera {} | nubes {}{}",
+            n.name, n.shader.name(), on_off(ShaderKind::Rings), on_off(ShaderKind::Moon),
+            on_off(ShaderKind::Atmosphere), on_off(ShaderKind::Clouds),
+            if paused { " | PAUSA" } else { "" })
+}
+
 fn update_camera(cam: &mut Camera, input: &Input, dt: f32) {
     let fwd   = (input.is_held(Action::MoveForward) as i32 - input.is_held(Action::MoveBackward) as i32) as f32;
     let right = (input.is_held(Action::MoveRight)   as i32 - input.is_held(Action::MoveLeft)    as i32) as f32;
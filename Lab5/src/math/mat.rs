@@ -34,6 +34,7 @@ impl Mat4 {
 // Mat4 * Mat4
 impl Mul for Mat4 {
     type Output = Mat4;
+    #[allow(clippy::needless_range_loop)]
     fn mul(self, o: Mat4) -> Mat4 {
         let mut r = [[0.0;4];4];
         for i in 0..4 {
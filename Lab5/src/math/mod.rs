@@ -1,5 +1,8 @@
+//! Álgebra lineal (`Vec2/3/4`, `Mat4`, transformaciones RH) y ruido procedural.
+
 pub mod vec;
 pub mod mat;
+pub mod noise;
 
 pub use vec::{Vec2, Vec3, Vec4};
 pub use mat::{Mat4, look_at_rh, perspective_rh, viewport, rotation_y};
\ No newline at end of file
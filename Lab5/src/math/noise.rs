@@ -2,18 +2,33 @@ use crate::math::Vec3;
 
 // NOISE TYPE SELECTOR
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum NoiseType {
+    #[default]
     Perlin,
     Simplex,
     Cellular,
 }
 
-impl Default for NoiseType {
-    fn default() -> Self { NoiseType::Perlin }
+impl NoiseType {
+    pub const ALL: [NoiseType; 3] = [NoiseType::Perlin, NoiseType::Simplex, NoiseType::Cellular];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NoiseType::Perlin   => "perlin",
+            NoiseType::Simplex  => "simplex",
+            NoiseType::Cellular => "cellular",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        Self::ALL.iter().copied().find(|n| n.name() == s)
+    }
 }
 
 
+
 // PERLIN NOISE (Original)
 #[inline]
 fn hash(x: i32, y: i32, z: i32) -> f32 {
@@ -159,18 +174,24 @@ pub fn simplex_3d(p: Vec3) -> f32 {
 // CELLULAR NOISE (Worley/Voronoi-like)
 
 #[inline]
+#[allow(clippy::excessive_precision)]
 fn cellular_hash3(p: Vec3) -> Vec3 {
     let x = p.x.sin() * 43758.5453;
     let y = p.y.sin() * 22578.1459;
     let z = p.z.sin() * 19642.3490;
     Vec3::new(
-        (x - x.floor()),
-        (y - y.floor()),
-        (z - z.floor())
+        x - x.floor(),
+        y - y.floor(),
+        z - z.floor()
     )
 }
 
 pub fn cellular_3d(p: Vec3) -> f32 {
+    cellular_3d_with(p, cellular_hash3)
+}
+
+/// Ruido celular con otro punto por celda: `point(celda)` da su posición en [0, 1)³
+pub fn cellular_3d_with(p: Vec3, point: impl Fn(Vec3) -> Vec3) -> f32 {
     let pi = Vec3::new(p.x.floor(), p.y.floor(), p.z.floor());
     let pf = Vec3::new(p.x - pi.x, p.y - pi.y, p.z - pi.z);
     
@@ -181,8 +202,8 @@ pub fn cellular_3d(p: Vec3) -> f32 {
         for j in -1..=1 {
             for k in -1..=1 {
                 let neighbor = Vec3::new(i as f32, j as f32, k as f32);
-                let point = cellular_hash3(pi + neighbor);
-                let diff = neighbor + point - pf;
+                let feature = point(pi + neighbor);
+                let diff = neighbor + feature - pf;
                 let dist = diff.length();
                 
                 min_dist = min_dist.min(dist);
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use crate::math::{Vec3, viewport};
+use crate::renderer::{Framebuffer, Uniforms, RenderQueue, ShadowMap, ToneMapping, Bloom, PostEffect};
+use crate::renderer::post::apply_chain;
+use crate::scene::{load_obj, Camera, Scene, SceneFile};
+use crate::shaders::ShaderLibrary;
+use crate::utils::cli::CliArgs;
+
+/// Cámara inicial compartida por el viewer y el modo headless
+pub fn default_camera(width: u32, height: u32) -> Camera {
+    Camera {
+        eye: Vec3::new(0.0, 0.0, 3.5),
+        center: Vec3::new(0.0, 0.0, 0.0),
+        aspect: width as f32 / height as f32,
+        ..Camera::default()
+    }
+}
+
+/// Uniforms base a partir de la cámara
+pub fn base_uniforms(cam: &Camera) -> Uniforms<'static> {
+    Uniforms {
+        view: cam.view(),
+        proj: cam.proj(),
+        camera_pos: cam.eye,
+        ..Uniforms::default()
+    }
+}
+
+/// Cámara alejada que encuadra el sistema completo
+pub fn system_camera(width: u32, height: u32) -> Camera {
+    Camera::new_pivot(Vec3::new(0.0, 5.0, 15.0), Vec3::ZERO, width as f32 / height as f32)
+}
+
+/// Escena según los argumentos: `--scene` carga un archivo, `--shader` un solo
+/// cuerpo centrado y sin ninguno de los dos el sistema solar de ejemplo
+pub fn build_scene(args: &CliArgs) -> Result<(Scene, Camera), String> {
+    if let Some(path) = &args.scene {
+        let file = SceneFile::load(path)?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        let scene = file.build(dir, &args.mesh).map_err(|e| format!("{}: {}", path, e))?;
+        let camera = file.camera(args.width as f32 / args.height as f32).map_err(|e| format!("{}: {}", path, e))?;
+        return Ok((scene, camera));
+    }
+
+    let mesh = load_obj(&args.mesh)?;
+    Ok(match args.shader {
+        Some(kind) => (Scene::single(mesh, kind), default_camera(args.width, args.height)),
+        None => (Scene::solar_system(mesh), system_camera(args.width, args.height)),
+    })
+}
+
+/// Renderiza el mapa de sombras, limpia los buffers, dibuja todos los nodos de la
+/// escena, promedia las muestras MSAA, aplica el post-proceso y resuelve HDR -> LDR
+pub fn render_frame(
+    fb: &mut Framebuffer,
+    scene: &Scene,
+    shaders: &ShaderLibrary,
+    uniforms: &Uniforms,
+    shadows: &mut ShadowMap,
+    post: &[&dyn PostEffect],
+    tm: &ToneMapping,
+) {
+    let vp = viewport(0.0, 0.0, fb.width as f32, fb.height as f32, 1.0);
+
+    scene.render_shadows(shaders, uniforms, shadows);
+    let uniforms = Uniforms { shadow: shadows.is_enabled().then_some(&*shadows), ..*uniforms };
+
+    fb.clear_color(scene.background);
+    fb.clear_depth();
+
+    let mut queue = RenderQueue::new();
+    scene.enqueue(shaders, &uniforms, &mut queue);
+    queue.flush(fb, vp);
+    fb.resolve_samples();
+
+    apply_chain(fb, post);
+    fb.resolve(tm);
+}
+
+/// Renderiza `args.frames` frames sin ventana y los guarda como PNG
+pub fn run(args: &CliArgs) -> Result<(), String> {
+    let (scene, cam) = build_scene(args)?;
+    let shaders = ShaderLibrary::new();
+    let mut uniforms = base_uniforms(&cam);
+    let mut fb = Framebuffer::with_msaa(args.width as usize, args.height as usize, args.msaa);
+    let bloom = Bloom { strength: args.bloom, ..Bloom::default() };
+    let mut shadows = ShadowMap::new(args.shadow_map);
+
+    println!("Headless: {}x{} (MSAA {}x), escena={} ({} nodos), tonemap={} ({:+.1} EV), bloom={:.2}, sombras={}px, {} frame(s)",
+             args.width, args.height, args.msaa.samples(), args.scene.as_deref().unwrap_or(args.shader.map_or("sistema", |k| k.name())), scene.nodes.len(),
+             args.tonemap.operator.name(), args.tonemap.exposure, args.bloom, args.shadow_map, args.frames);
+
+    for i in 0..args.frames {
+        uniforms.time = args.time + i as f32 * args.dt;
+        render_frame(&mut fb, &scene, &shaders, &uniforms, &mut shadows, &[&bloom], &args.tonemap);
+
+        let path = args.frame_path(i);
+        if let Some(dir) = Path::new(&path).parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("No pude crear '{}': {}", dir.display(), e))?;
+        }
+        fb.save_png(&path)?;
+        println!("Frame guardado: {} (t={:.3})", path, uniforms.time);
+    }
+    Ok(())
+}
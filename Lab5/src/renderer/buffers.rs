@@ -1,5 +1,9 @@
 use image::{RgbaImage, Rgba};
 
+use crate::math::{Vec3, Vec4};
+use super::state::RenderState;
+use super::tonemap::{ToneMapping, srgb_to_linear};
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Color { pub r: u8, pub g: u8, pub b: u8, pub a: u8 }
 
@@ -8,25 +12,103 @@ impl Color {
     pub const fn rgb(r: u8, g: u8, b: u8) -> Self { Self { r, g, b, a: 255 } }
 
     pub fn from_f32_rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::from_f32_rgba(r, g, b, 1.0)
+    }
+
+    pub fn from_f32_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
         fn to8(x: f32) -> u8 { (x.clamp(0.0, 1.0) * 255.0 + 0.5) as u8 }
-        Self::rgb(to8(r), to8(g), to8(b))
+        Self::rgba(to8(r), to8(g), to8(b), to8(a))
     }
 
     pub fn to_rgba(self) -> [u8;4] { [self.r, self.g, self.b, self.a] }
+
+    /// Color sRGB de 8 bits -> radiancia lineal (rgb) + alpha
+    pub fn to_linear(self) -> Vec4 {
+        let f = |c: u8| srgb_to_linear(c as f32 / 255.0);
+        Vec4::new(f(self.r), f(self.g), f(self.b), self.a as f32 / 255.0)
+    }
+}
+
+/// Muestras por pixel (MSAA). Cobertura, profundidad y color se guardan por muestra,
+/// pero el fragment se evalúa una sola vez por pixel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Msaa {
+    #[default]
+    Off,
+    X2,
+    X4,
+    X8,
+}
+
+impl Msaa {
+    pub const ALL: [Msaa; 4] = [Msaa::Off, Msaa::X2, Msaa::X4, Msaa::X8];
+
+    pub fn samples(self) -> usize {
+        match self {
+            Msaa::Off => 1,
+            Msaa::X2 => 2,
+            Msaa::X4 => 4,
+            Msaa::X8 => 8,
+        }
+    }
+
+    pub fn from_samples(n: usize) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| m.samples() == n)
+    }
+
+    /// Posición de cada muestra relativa al centro del pixel
+    /// (los patrones estándar de D3D, múltiplos de 1/16 de pixel)
+    pub fn positions(self) -> &'static [(f32, f32)] {
+        match self {
+            Msaa::Off => &[(0.0, 0.0)],
+            Msaa::X2 => &[(0.25, 0.25), (-0.25, -0.25)],
+            Msaa::X4 => &[(-0.125, -0.375), (0.375, -0.125), (-0.375, 0.125), (0.125, 0.375)],
+            Msaa::X8 => &[
+                (0.0625, -0.1875), (-0.0625, 0.1875), (0.3125, 0.0625), (-0.1875, -0.3125),
+                (-0.3125, 0.3125), (-0.4375, -0.0625), (0.1875, 0.4375), (0.4375, -0.4375),
+            ],
+        }
+    }
 }
 
+/// Destino del pipeline: el raster escribe radiancia lineal sin recortar en `hdr`
+/// (con MSAA en `samples`, y `resolve_samples` promedia hacia `hdr`);
+/// `resolve` aplica tonemapping + sRGB y deja el resultado de 8 bits en `color`.
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
-    pub color: Vec<Color>,
-    pub depth: Vec<f32>, 
+    pub msaa: Msaa,
+    pub hdr: Vec<Vec4>,     // rgb lineal (puede ser > 1) + alpha, por pixel
+    pub samples: Vec<Vec4>, // igual que `hdr` pero por muestra (vacío sin MSAA)
+    pub color: Vec<Color>,  // salida LDR para pantalla/PNG
+    pub depth: Vec<f32>,    // por muestra: pixel i, muestra s en i*n + s
 }
 
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::with_msaa(width, height, Msaa::Off)
+    }
+
+    pub fn with_msaa(width: usize, height: usize, msaa: Msaa) -> Self {
+        let n = msaa.samples();
+        let clear = Vec4::new(0.0, 0.0, 0.0, 1.0);
         Self {
-            width, height,
+            width, height, msaa,
+            hdr: vec![clear; width*height],
+            samples: if n > 1 { vec![clear; width*height*n] } else { Vec::new() },
             color: vec![Color::rgb(0,0,0); width*height],
+            depth: vec![f32::INFINITY; width*height*n],
+        }
+    }
+
+    /// Sólo profundidad (mapas de sombra): sin buffers de color; dibujar con
+    /// `RenderState::shadow_caster()` (`Tile::put_samples` lo verifica en debug)
+    pub fn depth_only(width: usize, height: usize) -> Self {
+        Self {
+            width, height, msaa: Msaa::Off,
+            hdr: Vec::new(),
+            samples: Vec::new(),
+            color: Vec::new(),
             depth: vec![f32::INFINITY; width*height],
         }
     }
@@ -39,6 +121,8 @@ impl Framebuffer {
     }
 
     pub fn clear_color(&mut self, c: Color) {
+        self.hdr.fill(c.to_linear());
+        self.samples.fill(c.to_linear());
         self.color.fill(c);
     }
 
@@ -46,16 +130,54 @@ impl Framebuffer {
         self.depth.fill(f32::INFINITY);
     }
 
-    #[inline]
-    pub fn put_pixel(&mut self, x: i32, y: i32, z: f32, c: Color) {
-        if let Some(i) = self.idx(x, y) {
-            if z < self.depth[i] {
-                self.depth[i] = z;
-                self.color[i] = c;
-            }
+    /// Promedia las muestras de cada pixel hacia `hdr` (sin MSAA no hace nada).
+    /// Va después de dibujar y antes del post-proceso.
+    pub fn resolve_samples(&mut self) {
+        let n = self.msaa.samples();
+        if n == 1 { return; }
+        let k = 1.0 / n as f32;
+        for (dst, src) in self.hdr.iter_mut().zip(self.samples.chunks_exact(n)) {
+            *dst = src.iter().fold(Vec4::new(0.0, 0.0, 0.0, 0.0), |acc, &c| acc + c) * k;
+        }
+    }
+
+    /// HDR -> LDR: tonemapping + codificación sRGB hacia `color`
+    pub fn resolve(&mut self, tm: &ToneMapping) {
+        for (dst, src) in self.color.iter_mut().zip(&self.hdr) {
+            let c = tm.map(Vec3::new(src.x, src.y, src.z));
+            *dst = Color::from_f32_rgb(c.x, c.y, c.z);
         }
     }
 
+    /// Buffer de color donde escribe el raster: `hdr` o, con MSAA, `samples`
+    fn target(&mut self) -> (&mut [Vec4], &mut [f32]) {
+        if self.msaa == Msaa::Off { (&mut self.hdr, &mut self.depth) } else { (&mut self.samples, &mut self.depth) }
+    }
+
+    /// Vista mutable de todo el framebuffer como un único tile
+    pub fn full_tile(&mut self) -> Tile<'_> {
+        let (rows, width, msaa) = (self.height, self.width, self.msaa);
+        let (hdr, depth) = self.target();
+        Tile { y0: 0, rows, width, msaa, hdr, depth }
+    }
+
+    /// Divide el framebuffer en franjas horizontales de `rows` filas (memoria contigua y disjunta)
+    pub fn tiles_mut(&mut self, rows: usize) -> Vec<Tile<'_>> {
+        let rows = rows.max(1);
+        let (width, msaa) = (self.width, self.msaa);
+        let n = msaa.samples();
+        if width == 0 { return Vec::new(); }
+        let (hdr, depth) = self.target();
+        // Sin color (`depth_only`) cada tile recibe un slice vacío
+        let hdr = hdr.chunks_mut(width * rows * n).chain(std::iter::repeat_with(|| &mut [][..]));
+        depth.chunks_mut(width * rows * n)
+            .zip(hdr)
+            .enumerate()
+            .map(|(i, (depth, hdr))| Tile { y0: i * rows, rows: depth.len() / (width * n), width, msaa, hdr, depth })
+            .collect()
+    }
+
+    /// Guarda `color` (llamar a `resolve` antes)
     pub fn save_png(&self, path: &str) -> Result<(), String> {
         let mut img = RgbaImage::new(self.width as u32, self.height as u32);
         for y in 0..self.height {
@@ -68,3 +190,36 @@ impl Framebuffer {
         img.save(path).map_err(|e| format!("No pude guardar '{}': {}", path, e))
     }
 }
+
+/// Franja de filas `[y0, y0+rows)` del framebuffer; las coordenadas son absolutas
+pub struct Tile<'a> {
+    pub y0: usize,
+    pub rows: usize,
+    pub width: usize,
+    pub msaa: Msaa,
+    hdr: &'a mut [Vec4], // por muestra
+    depth: &'a mut [f32],
+}
+
+impl Tile<'_> {
+    /// Depth test + mezcla + escritura de profundidad según `state`, en cada muestra
+    /// marcada en `mask` (bit s = muestra s) con su profundidad `z[s]`.
+    /// Todas reciben el mismo color `c`: se sombrea una vez por pixel.
+    #[inline]
+    pub fn put_samples(&mut self, x: i32, y: i32, mask: u32, z: &[f32], c: Vec4, state: &RenderState) {
+        debug_assert!(!state.color_write || !self.hdr.is_empty(), "color_write sobre un framebuffer sin color (depth_only)");
+        if x < 0 || y < self.y0 as i32 { return; }
+        let (x, y) = (x as usize, y as usize - self.y0);
+        if x >= self.width || y >= self.rows { return; }
+        let n = self.msaa.samples();
+        let base = (y*self.width + x) * n;
+        for (s, &zs) in z.iter().enumerate().take(n) {
+            if mask & (1 << s) == 0 { continue; }
+            let i = base + s;
+            if zs < self.depth[i] {
+                if state.depth_write { self.depth[i] = zs; }
+                if state.color_write { self.hdr[i] = state.blend.blend(c, self.hdr[i]); }
+            }
+        }
+    }
+}
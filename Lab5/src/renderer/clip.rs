@@ -0,0 +1,166 @@
+use crate::math::Vec4;
+
+use super::raster::{Interp, Varyings};
+
+/// Vértice en clip space (antes de dividir por w) con sus varyings
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ClipVertex {
+    pub pos: Vec4,
+    pub vary: Varyings,
+}
+
+impl ClipVertex {
+    /// Interpolación lineal en clip space (correcta antes de la división perspectiva).
+    /// Los varyings `NoPerspective` se interpolan con el parámetro equivalente en pantalla.
+    fn lerp(a: &ClipVertex, b: &ClipVertex, t: f32, layout: &[Interp]) -> ClipVertex {
+        let pos = a.pos + (b.pos - a.pos) * t;
+        let ts = if pos.w != 0.0 { t * b.pos.w / pos.w } else { t };
+        let mut custom = a.vary.custom;
+        for (k, interp) in layout.iter().enumerate() {
+            let (ca, cb) = (a.vary.custom[k], b.vary.custom[k]);
+            custom[k] = match interp {
+                Interp::Perspective => ca + (cb - ca) * t,
+                Interp::NoPerspective => ca + (cb - ca) * ts,
+                Interp::Flat => ca, // se reemplaza por el del vértice provocador
+            };
+        }
+        ClipVertex {
+            pos,
+            vary: Varyings {
+                pos_ws: a.vary.pos_ws + (b.vary.pos_ws - a.vary.pos_ws) * t,
+                nrm_ws: a.vary.nrm_ws + (b.vary.nrm_ws - a.vary.nrm_ws) * t,
+                uv: a.vary.uv + (b.vary.uv - a.vary.uv) * t,
+                custom,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Los seis planos del frustum en coordenadas homogéneas (-w <= x,y,z <= w)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Plane { Near, Far, Left, Right, Bottom, Top }
+
+impl Plane {
+    pub const ALL: [Plane; 6] = [Plane::Near, Plane::Far, Plane::Left, Plane::Right, Plane::Bottom, Plane::Top];
+
+    /// Distancia con signo al plano: >= 0 significa "dentro"
+    #[inline]
+    pub fn dist(self, p: Vec4) -> f32 {
+        match self {
+            Plane::Near   => p.z + p.w,
+            Plane::Far    => p.w - p.z,
+            Plane::Left   => p.x + p.w,
+            Plane::Right  => p.w - p.x,
+            Plane::Bottom => p.y + p.w,
+            Plane::Top    => p.w - p.y,
+        }
+    }
+}
+
+/// Bitmask de planos que deja fuera al punto
+#[inline]
+fn outcode(p: Vec4) -> u8 {
+    let mut code = 0;
+    for (i, plane) in Plane::ALL.iter().enumerate() {
+        if plane.dist(p) < 0.0 { code |= 1 << i; }
+    }
+    code
+}
+
+/// Recorta un triángulo contra el frustum (Sutherland-Hodgman en clip space).
+/// Deja en `out` un polígono convexo (0 o 3..=9 vértices) listo para triangular en abanico.
+/// El raster lee los varyings `Flat` del primer vértice de cada triángulo, así que si hubo
+/// recorte todos los vértices del polígono toman los de `tri[0]` (el vértice provocador).
+pub fn clip_triangle(tri: [ClipVertex; 3], layout: &[Interp], out: &mut Vec<ClipVertex>) {
+    out.clear();
+
+    let codes = [outcode(tri[0].pos), outcode(tri[1].pos), outcode(tri[2].pos)];
+    // Todos fuera del mismo plano: descartado
+    if codes[0] & codes[1] & codes[2] != 0 { return; }
+    // Todos dentro: sin recorte
+    out.extend_from_slice(&tri);
+    if codes[0] | codes[1] | codes[2] == 0 { return; }
+
+    let mut tmp: Vec<ClipVertex> = Vec::with_capacity(9);
+    for plane in Plane::ALL {
+        if out.is_empty() { return; }
+        std::mem::swap(out, &mut tmp);
+        out.clear();
+
+        let n = tmp.len();
+        for i in 0..n {
+            let a = &tmp[i];
+            let b = &tmp[(i + 1) % n];
+            let da = plane.dist(a.pos);
+            let db = plane.dist(b.pos);
+
+            if da >= 0.0 { out.push(*a); }
+            if (da >= 0.0) != (db >= 0.0) {
+                let t = da / (da - db);
+                out.push(ClipVertex::lerp(a, b, t, layout));
+            }
+        }
+    }
+
+    if out.len() < 3 { out.clear(); }
+    for (k, interp) in layout.iter().enumerate() {
+        if *interp == Interp::Flat {
+            for v in out.iter_mut() { v.vary.custom[k] = tri[0].vary.custom[k]; }
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec3;
+
+    fn cv(x: f32, y: f32, z: f32, w: f32) -> ClipVertex {
+        ClipVertex { pos: Vec4::new(x, y, z, w), vary: Varyings { pos_ws: Vec3::new(z, 0.0, 0.0), ..Default::default() } }
+    }
+
+    #[test]
+    fn near_plane_generates_interpolated_vertices() {
+        // v0 detrás de la cámara, v1/v2 delante
+        let tri = [cv(0.0, 0.0, -2.0, 1.0), cv(-0.5, 0.0, 0.0, 1.0), cv(0.5, 0.0, 0.0, 1.0)];
+        let mut out = Vec::new();
+        clip_triangle(tri, &[], &mut out);
+
+        assert_eq!(out.len(), 4);
+        for v in &out {
+            assert!(Plane::Near.dist(v.pos) >= -1e-6);
+            // pos_ws.x copia z: la interpolación debe seguir a la posición
+            assert!((v.vary.pos_ws.x - v.pos.z).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn clipped_vertices_follow_qualifiers() {
+        // a queda detrás del plano near; sobre a-b el corte cae en t = 0.25 (clip space),
+        // que en pantalla es la mitad del segmento
+        let with = |mut v: ClipVertex, c: [f32; 3]| { v.vary.custom[..3].copy_from_slice(&c); v };
+        let tri = [
+            with(cv(0.0, 0.0, -2.0, 1.0), [0.0, 0.0, 5.0]),
+            with(cv(1.0, 0.0, 0.0, 3.0), [1.0, 1.0, 6.0]),
+            with(cv(0.0, 1.0, 0.0, 3.0), [0.0, 0.0, 6.0]),
+        ];
+        let mut out = Vec::new();
+        clip_triangle(tri, &[Interp::Perspective, Interp::NoPerspective, Interp::Flat], &mut out);
+
+        let v = out.iter().find(|v| (v.pos.x - 0.25).abs() < 1e-6 && v.pos.y.abs() < 1e-6).unwrap();
+        assert!((v.vary.custom[0] - 0.25).abs() < 1e-6);
+        assert!((v.vary.custom[1] - 0.5).abs() < 1e-6);
+        assert!(out.iter().all(|v| v.vary.custom[2] == 5.0));
+    }
+
+    #[test]
+    fn trivial_accept_and_reject() {
+        let mut out = Vec::new();
+        clip_triangle([cv(0.0, 0.0, 0.0, 1.0), cv(0.5, 0.0, 0.0, 1.0), cv(0.0, 0.5, 0.0, 1.0)], &[], &mut out);
+        assert_eq!(out.len(), 3);
+        clip_triangle([cv(2.0, 0.0, 0.0, 1.0), cv(3.0, 0.0, 0.0, 1.0), cv(2.0, 0.5, 0.0, 1.0)], &[], &mut out);
+        assert!(out.is_empty());
+    }
+}
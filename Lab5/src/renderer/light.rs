@@ -0,0 +1,83 @@
+use crate::math::Vec3;
+
+/// Cantidad máxima de luces por draw
+pub const MAX_LIGHTS: usize = 4;
+
+/// Tipo de luz
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightKind {
+    /// Luz en el infinito; `dir` apunta hacia la luz
+    Directional { dir: Vec3 },
+    /// Luz en `pos`, atenuada por 1 / (1 + falloff·d²) (0 = sin atenuación)
+    Point { pos: Vec3, falloff: f32 },
+}
+
+/// Una luz: tipo, color (lineal) e intensidad
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Vec3,
+    pub intensity: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self::directional(Vec3::new(0.5, 0.7, 0.2), Vec3::from_scalar(1.0), 1.0)
+    }
+}
+
+impl Light {
+    pub fn directional(dir: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self { kind: LightKind::Directional { dir: dir.normalize() }, color, intensity }
+    }
+
+    pub fn point(pos: Vec3, color: Vec3, intensity: f32, falloff: f32) -> Self {
+        Self { kind: LightKind::Point { pos, falloff }, color, intensity }
+    }
+
+    /// Dirección (normalizada) desde `p` hacia la luz y radiancia que llega a `p`
+    #[inline]
+    pub fn incident(&self, p: Vec3) -> (Vec3, Vec3) {
+        let radiance = self.color * self.intensity;
+        match self.kind {
+            LightKind::Directional { dir } => (dir, radiance),
+            LightKind::Point { pos, falloff } => {
+                let d = pos - p;
+                let d2 = d.dot(d);
+                (d.normalize(), radiance * (1.0 / (1.0 + falloff * d2)))
+            }
+        }
+    }
+}
+
+/// Lista de luces de un draw (tamaño fijo para que `Uniforms` siga siendo `Copy`).
+/// La primera es la principal: la que proyecta el mapa de sombras.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Lights {
+    items: [Light; MAX_LIGHTS],
+    len: usize,
+}
+
+impl Lights {
+    pub fn single(light: Light) -> Self {
+        let mut out = Self::default();
+        out.push(light);
+        out
+    }
+
+    /// Agrega una luz; devuelve false (y la ignora) si ya hay `MAX_LIGHTS`
+    pub fn push(&mut self, light: Light) -> bool {
+        if self.len == MAX_LIGHTS { return false; }
+        self.items[self.len] = light;
+        self.len += 1;
+        true
+    }
+
+    pub fn as_slice(&self) -> &[Light] { &self.items[..self.len] }
+    pub fn iter(&self) -> std::slice::Iter<'_, Light> { self.as_slice().iter() }
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// La luz principal (la del mapa de sombras)
+    pub fn main(&self) -> Option<&Light> { self.as_slice().first() }
+}
@@ -1,8 +1,25 @@
+//! Pipeline de rasterización por software: framebuffer, uniforms, raster y `draw_mesh`.
+
 pub mod uniforms;
 pub mod buffers;
 pub mod raster;
+pub mod clip;
+pub mod state;
+pub mod queue;
+pub mod tonemap;
+pub mod post;
 pub mod pipeline;
+pub mod texture;
+pub mod shadow;
+pub mod light;
 
 
-pub use uniforms::{Uniforms, PlanetParams};
-pub use buffers::Framebuffer;
+pub use uniforms::{Uniforms, PlanetParams, AtmosphereParams, CloudParams};
+pub use buffers::{Framebuffer, Msaa};
+pub use state::{RenderState, CullMode, FrontFace, BlendMode};
+pub use queue::RenderQueue;
+pub use tonemap::{Tonemap, ToneMapping};
+pub use post::{PostEffect, Bloom};
+pub use texture::{Texture2D, TextureSet, Sampler, Filter, Wrap};
+pub use shadow::ShadowMap;
+pub use light::{Light, LightKind, Lights, MAX_LIGHTS};
@@ -1,7 +1,11 @@
+use std::sync::Mutex;
+
 use crate::math::{Vec2, Vec3, Vec4, Mat4};
 use crate::scene::Mesh;
-use super::buffers::{Framebuffer, Color};
-use super::raster::{RasterInput, Varyings, raster_triangle};
+use super::buffers::{Framebuffer, Tile};
+use super::clip::{ClipVertex, clip_triangle};
+use super::raster::{Interp, MAX_VARYINGS, RasterInput, Varyings, raster_triangle, row_span, signed_area};
+use super::state::RenderState;
 use super::uniforms::Uniforms;
 
 /// Entrada al vertex shader
@@ -13,87 +17,184 @@ pub struct VertexIn {
 }
 
 /// Salida del vertex shader
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct VertexOut {
     pub clip_pos: Vec4, 
     pub pos_ws: Vec3,  
     pub nrm_ws: Vec3,
     pub uv: Vec2,
+    pub custom: [f32; MAX_VARYINGS], // varyings propios (ver `Shader::varyings`)
 }
 
 
-pub trait Shader {
+/// Filas por tile del rasterizador multihilo
+pub const TILE_ROWS: usize = 16;
+
+/// Los shaders son inmutables durante el draw: el fragment se evalúa en paralelo desde varios hilos.
+pub trait Shader: Send + Sync {
     fn name(&self) -> &'static str { "UnnamedShader" }
 
+    /// Calificadores de los varyings propios que usa: el slot `i` es `custom[i]` en
+    /// `VertexOut` y en `Varyings`. Los slots que no figuran no se interpolan.
+    fn varyings(&self) -> &'static [Interp] { &[] }
+
     /// Vertex: recibe atributos por-vértice + uniforms, devuelve clip_pos y varyings
-    fn vertex(&mut self, vin: VertexIn, uniforms: &Uniforms) -> VertexOut;
+    fn vertex(&self, vin: VertexIn, uniforms: &Uniforms) -> VertexOut;
 
-    /// Fragment: recibe varyings interpolados + uniforms y devuelve Color
-    fn fragment(&mut self, vary: &Varyings, uniforms: &Uniforms) -> Color;
+    /// Fragment: recibe varyings interpolados (con sus derivadas `dfdx`/`dfdy` en pantalla)
+    /// y uniforms; devuelve radiancia lineal (rgb, sin recortar: puede superar 1 para
+    /// emisivos) + alpha en `w`
+    fn fragment(&self, vary: &Varyings, uniforms: &Uniforms) -> Vec4;
+
+    /// Luz que el cuerpo emite hacia la escena: color (máximo canal = 1) e intensidad.
+    /// `None` si no ilumina a los demás (el default)
+    fn emitted_light(&self, _uniforms: &Uniforms) -> Option<(Vec3, f32)> { None }
 }
 
 pub fn draw_mesh(
     fb: &mut Framebuffer,
     mesh: &Mesh,
-    shader: &mut dyn Shader,
+    shader: &dyn Shader,
     uniforms: &Uniforms,
     viewport: Mat4,
+    state: &RenderState,
 ) {
-    let _mvp = uniforms.proj * uniforms.view * uniforms.model;
-
     // Vertex stage
-    let mut clip_positions: Vec<Vec4> = Vec::with_capacity(mesh.vertices.len());
-    let mut vary_buff: Vec<(Vec3, Vec3, Vec2)> = Vec::with_capacity(mesh.vertices.len());
+    let mut verts: Vec<ClipVertex> = Vec::with_capacity(mesh.vertices.len());
 
     for v in &mesh.vertices {
         let vin = VertexIn {
             pos: v.pos,
             nrm: v.nrm,
-            uv:  Vec2::new(0.0, 0.0),
+            uv:  v.uv,
         };
         let vout = shader.vertex(vin, uniforms);
-        clip_positions.push(vout.clip_pos);
-        vary_buff.push((vout.pos_ws, vout.nrm_ws, vout.uv));
+        verts.push(ClipVertex {
+            pos: vout.clip_pos,
+            vary: Varyings { pos_ws: vout.pos_ws, nrm_ws: vout.nrm_ws, uv: vout.uv, custom: vout.custom, ..Default::default() },
+        });
     }
 
+    // Clipping + setup + culling
+    let layout = shader.varyings();
+    let mut tris: Vec<RasterInput> = Vec::with_capacity(mesh.indices.len());
+    let mut poly: Vec<ClipVertex> = Vec::with_capacity(9);
+
     for tri in &mesh.indices {
-        let idx = [tri.i0 as usize, tri.i1 as usize, tri.i2 as usize];
-
-        // Clip coordinates
-        let cp = [clip_positions[idx[0]], clip_positions[idx[1]], clip_positions[idx[2]]];
-
-        if cp.iter().any(|p| p.w <= 0.0) { continue; }
-
-        // NDC
-        let ndc = [
-            Vec4::new(cp[0].x/cp[0].w, cp[0].y/cp[0].w, cp[0].z/cp[0].w, 1.0),
-            Vec4::new(cp[1].x/cp[1].w, cp[1].y/cp[1].w, cp[1].z/cp[1].w, 1.0),
-            Vec4::new(cp[2].x/cp[2].w, cp[2].y/cp[2].w, cp[2].z/cp[2].w, 1.0),
-        ];
-
-        // Viewport 
-        let sp = [
-            viewport * ndc[0],
-            viewport * ndc[1],
-            viewport * ndc[2],
-        ];
-
-        let z = [sp[0].z, sp[1].z, sp[2].z];
-        let inv_w = [1.0/cp[0].w, 1.0/cp[1].w, 1.0/cp[2].w];
-
-        // Varyings
-        let v0 = vary_buff[idx[0]];
-        let v1 = vary_buff[idx[1]];
-        let v2 = vary_buff[idx[2]];
-        let v = [
-            Varyings { pos_ws: v0.0, nrm_ws: v0.1.normalize(), uv: v0.2 },
-            Varyings { pos_ws: v1.0, nrm_ws: v1.1.normalize(), uv: v1.2 },
-            Varyings { pos_ws: v2.0, nrm_ws: v2.1.normalize(), uv: v2.2 },
-        ];
-
-        let rin = RasterInput { p: sp, z, inv_w, v };
-        raster_triangle(fb, &rin, |_x, _y, _z, vary| {
-            shader.fragment(&vary, uniforms)
+        let cv = [verts[tri.i0 as usize], verts[tri.i1 as usize], verts[tri.i2 as usize]];
+
+        // Recorte contra el frustum; el polígono resultante se triangula en abanico
+        clip_triangle(cv, layout, &mut poly);
+        for i in 2..poly.len() {
+            let rin = setup_triangle([poly[0], poly[i - 1], poly[i]], viewport);
+
+            // Culling por área con signo en pantalla
+            let area = signed_area((rin.p[0].x, rin.p[0].y), (rin.p[1].x, rin.p[1].y), (rin.p[2].x, rin.p[2].y));
+            if state.culls(area) { continue; }
+
+            tris.push(rin);
+        }
+    }
+
+    let threads = state.thread_count();
+    if threads <= 1 || fb.height <= TILE_ROWS {
+        raster_tile(&mut fb.full_tile(), tris.iter(), shader, uniforms, state);
+        return;
+    }
+
+    // Binning: cada tile guarda (en orden de envío) los triángulos que lo tocan,
+    // así el resultado por pixel es idéntico al camino de un solo hilo
+    let tiles = fb.tiles_mut(TILE_ROWS);
+    let mut bins: Vec<Vec<u32>> = vec![Vec::new(); tiles.len()];
+    for (i, t) in tris.iter().enumerate() {
+        let (y0, y1) = row_span(t);
+        if y1 < 0 { continue; }
+        let first = (y0.max(0) as usize) / TILE_ROWS;
+        let last = (y1 as usize / TILE_ROWS).min(bins.len() - 1);
+        for bin in bins.iter_mut().take(last + 1).skip(first) {
+            bin.push(i as u32);
+        }
+    }
+
+    let work = Mutex::new(tiles.into_iter().zip(bins).filter(|(_, b)| !b.is_empty()));
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let next = work.lock().unwrap().next();
+                match next {
+                    Some((mut tile, bin)) => {
+                        raster_tile(&mut tile, bin.iter().map(|&i| &tris[i as usize]), shader, uniforms, state)
+                    }
+                    None => break,
+                }
+            });
+        }
+    });
+}
+
+/// Rasteriza y sombrea en `tile` los triángulos dados (en ese orden)
+fn raster_tile<'a>(
+    tile: &mut Tile,
+    tris: impl Iterator<Item = &'a RasterInput>,
+    shader: &dyn Shader,
+    uniforms: &Uniforms,
+    state: &RenderState,
+) {
+    for tri in tris {
+        raster_triangle(tile, tri, shader.varyings(), state, |_x, _y, _z, vary| {
+            // Sin color (pasada de sombras) no hace falta el fragment
+            if state.color_write { shader.fragment(&vary, uniforms) } else { Vec4::ZERO }
         });
     }
-}
\ No newline at end of file
+}
+
+/// División perspectiva + viewport de un triángulo ya recortado
+fn setup_triangle(cv: [ClipVertex; 3], viewport: Mat4) -> RasterInput {
+    // NDC
+    let ndc = cv.map(|c| Vec4::new(c.pos.x/c.pos.w, c.pos.y/c.pos.w, c.pos.z/c.pos.w, 1.0));
+
+    // Viewport 
+    let sp = ndc.map(|n| viewport * n);
+
+    RasterInput {
+        p: sp,
+        z: [sp[0].z, sp[1].z, sp[2].z],
+        inv_w: [1.0/cv[0].pos.w, 1.0/cv[1].pos.w, 1.0/cv[2].pos.w],
+        v: cv.map(|c| Varyings { nrm_ws: c.vary.nrm_ws.normalize(), ..c.vary }),
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{viewport, Vec3};
+    use crate::renderer::buffers::Msaa;
+    use crate::renderer::uniforms::test_uniforms;
+    use crate::scene::load_obj;
+    use crate::shaders::star::Star;
+
+    #[test]
+    fn tiled_matches_single_thread() {
+        let mesh = load_obj("assets/sphere.obj").unwrap();
+        let (w, h) = (160, 90);
+        let mut u = test_uniforms(w as f32 / h as f32);
+        u.time = 1.3;
+        u.model = crate::math::mat::scale(Vec3::new(1.2, 1.2, 1.2));
+        let vp = viewport(0.0, 0.0, w as f32, h as f32, 1.0);
+
+        let render = |threads, msaa| {
+            let mut fb = Framebuffer::with_msaa(w, h, msaa);
+            let state = RenderState { threads, ..RenderState::opaque() };
+            draw_mesh(&mut fb, &mesh, &Star, &u, vp, &state);
+            fb.resolve_samples();
+            fb
+        };
+        for msaa in [Msaa::Off, Msaa::X4] {
+            let a = render(1, msaa);
+            let b = render(4, msaa);
+            assert!(a.hdr.iter().zip(&b.hdr).all(|(x, y)| x == y));
+            assert!(a.depth.iter().zip(&b.depth).all(|(x, y)| x.to_bits() == y.to_bits()));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::math::Vec3;
+use super::buffers::Framebuffer;
+use super::texture::downsample_2x2;
+
+/// Efecto de post-proceso sobre el framebuffer HDR (después de `draw_mesh`, antes de `resolve`)
+pub trait PostEffect: Send + Sync {
+    fn name(&self) -> &'static str { "UnnamedEffect" }
+    fn apply(&self, fb: &mut Framebuffer);
+}
+
+/// Aplica los efectos en orden
+pub fn apply_chain(fb: &mut Framebuffer, effects: &[&dyn PostEffect]) {
+    for e in effects {
+        e.apply(fb);
+    }
+}
+
+/// Bloom: bright-pass, blur gaussiano separable en varios niveles reducidos y suma aditiva
+#[derive(Copy, Clone, Debug)]
+pub struct Bloom {
+    pub threshold: f32, // radiancia a partir de la cual algo "brilla"
+    pub knee: f32,      // ancho de la transición suave del umbral
+    pub strength: f32,  // intensidad de la suma final (0 = apagado)
+    pub levels: usize,  // niveles de la pirámide (cada uno a la mitad de resolución)
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self { threshold: 0.9, knee: 0.5, strength: 0.6, levels: 5 }
+    }
+}
+
+impl Bloom {
+    /// Cuánto de `c` pasa el umbral (soft knee)
+    #[inline]
+    fn bright(&self, c: Vec3) -> Vec3 {
+        let lum = c.x.max(c.y).max(c.z);
+        let soft = (lum - self.threshold + self.knee).clamp(0.0, 2.0 * self.knee);
+        let soft = soft * soft / (4.0 * self.knee + 1e-5);
+        let w = soft.max(lum - self.threshold) / lum.max(1e-5);
+        c * w.max(0.0)
+    }
+}
+
+impl PostEffect for Bloom {
+    fn name(&self) -> &'static str { "Bloom" }
+
+    fn apply(&self, fb: &mut Framebuffer) {
+        if self.strength <= 0.0 || self.levels == 0 || fb.width < 2 || fb.height < 2 { return; }
+
+        // Nivel 0: bright-pass a media resolución
+        let full = Plane {
+            w: fb.width,
+            h: fb.height,
+            px: fb.hdr.iter().map(|c| self.bright(c.xyz())).collect(),
+        };
+        let mut levels = vec![full.downsample()];
+        while levels.len() < self.levels {
+            let last = levels.last().unwrap();
+            if last.w < 4 || last.h < 4 { break; }
+            levels.push(last.downsample());
+        }
+        for l in &mut levels {
+            l.blur();
+        }
+
+        // Composite aditivo: suma de todos los niveles re-escalados
+        let k = self.strength / levels.len() as f32;
+        for y in 0..fb.height {
+            let v = (y as f32 + 0.5) / fb.height as f32;
+            for x in 0..fb.width {
+                let u = (x as f32 + 0.5) / fb.width as f32;
+                let mut sum = Vec3::ZERO;
+                for l in &levels {
+                    sum += l.sample(u, v);
+                }
+                let c = &mut fb.hdr[y*fb.width + x];
+                c.x += sum.x * k;
+                c.y += sum.y * k;
+                c.z += sum.z * k;
+            }
+        }
+    }
+}
+
+/// Imagen RGB flotante auxiliar
+struct Plane {
+    w: usize,
+    h: usize,
+    px: Vec<Vec3>,
+}
+
+/// Pesos de un gaussiano de 9 taps (sigma ~ 2), normalizados
+const GAUSS: [f32; 5] = [0.2026, 0.1790, 0.1240, 0.0672, 0.0285];
+
+impl Plane {
+    #[inline]
+    fn at(&self, x: i32, y: i32) -> Vec3 {
+        let x = x.clamp(0, self.w as i32 - 1) as usize;
+        let y = y.clamp(0, self.h as i32 - 1) as usize;
+        self.px[y*self.w + x]
+    }
+
+    /// Mitad de resolución (`downsample_2x2`)
+    fn downsample(&self) -> Plane {
+        let (w, h, px) = downsample_2x2(self.w, self.h, &self.px);
+        Plane { w, h, px }
+    }
+
+    /// Blur gaussiano separable (horizontal y luego vertical)
+    fn blur(&mut self) {
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let mut out = Vec::with_capacity(self.px.len());
+            for y in 0..self.h as i32 {
+                for x in 0..self.w as i32 {
+                    let mut s = self.at(x, y) * GAUSS[0];
+                    for (i, &g) in GAUSS.iter().enumerate().skip(1) {
+                        let i = i as i32;
+                        s += (self.at(x + dx*i, y + dy*i) + self.at(x - dx*i, y - dy*i)) * g;
+                    }
+                    out.push(s);
+                }
+            }
+            self.px = out;
+        }
+    }
+
+    /// Muestreo bilineal con coordenadas normalizadas
+    fn sample(&self, u: f32, v: f32) -> Vec3 {
+        let fx = u * self.w as f32 - 0.5;
+        let fy = v * self.h as f32 - 0.5;
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let a = self.at(x0, y0) * (1.0 - tx) + self.at(x0 + 1, y0) * tx;
+        let b = self.at(x0, y0 + 1) * (1.0 - tx) + self.at(x0 + 1, y0 + 1) * tx;
+        a * (1.0 - ty) + b * ty
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec4;
+
+    fn frame(bright: Vec3) -> Framebuffer {
+        let mut fb = Framebuffer::new(32, 32);
+        fb.hdr.fill(Vec4::new(0.3, 0.3, 0.3, 1.0));
+        fb.hdr[16*32 + 16] = Vec4::from3(bright, 1.0);
+        fb
+    }
+
+    #[test]
+    fn bloom_spreads_only_bright_pixels() {
+        let bloom = Bloom::default();
+        // Todo bajo el umbral (menos el knee): queda igual
+        let mut dim = frame(Vec3::from_scalar(0.3));
+        let before = dim.hdr.clone();
+        apply_chain(&mut dim, &[&bloom]);
+        assert_eq!(dim.hdr, before);
+
+        // Un pixel muy brillante ilumina a sus vecinos
+        let mut hot = frame(Vec3::from_scalar(50.0));
+        apply_chain(&mut hot, &[&bloom]);
+        for (x, y) in [(18, 16), (16, 13), (14, 14)] {
+            assert!(hot.hdr[y*32 + x].x > 0.3, "({}, {}): {:?}", x, y, hot.hdr[y*32 + x]);
+        }
+
+        // Con strength 0 no hace nada
+        let mut off = frame(Vec3::from_scalar(50.0));
+        let before = off.hdr.clone();
+        apply_chain(&mut off, &[&Bloom { strength: 0.0, ..bloom }]);
+        assert_eq!(off.hdr, before);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::math::{Mat4, Vec3, Vec4};
+use crate::scene::Mesh;
+use super::buffers::Framebuffer;
+use super::pipeline::{draw_mesh, Shader};
+use super::state::RenderState;
+use super::uniforms::Uniforms;
+
+/// Un `draw_mesh` pendiente
+pub struct DrawCall<'a> {
+    pub mesh: &'a Mesh,
+    pub shader: &'a dyn Shader,
+    pub uniforms: Uniforms<'a>,
+    pub state: RenderState,
+}
+
+impl DrawCall<'_> {
+    /// Profundidad en espacio vista del origen del modelo (más negativo = más lejos)
+    fn view_depth(&self) -> f32 {
+        let u = &self.uniforms;
+        (u.view * u.model * Vec4::new(0.0, 0.0, 0.0, 1.0)).z
+    }
+
+    /// Escala del modelo (largo de su primera columna)
+    fn scale(&self) -> f32 {
+        let m = &self.uniforms.model.m;
+        Vec3::new(m[0][0], m[1][0], m[2][0]).length()
+    }
+}
+
+/// Cola de draws de un frame: primero los opacos en orden de envío y luego
+/// los transparentes ordenados de atrás hacia adelante para que mezclen bien.
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    calls: Vec<DrawCall<'a>>,
+}
+
+impl<'a> RenderQueue<'a> {
+    pub fn new() -> Self { Self { calls: Vec::new() } }
+
+    pub fn push(&mut self, mesh: &'a Mesh, shader: &'a dyn Shader, uniforms: Uniforms<'a>, state: RenderState) {
+        self.calls.push(DrawCall { mesh, shader, uniforms, state });
+    }
+
+    pub fn len(&self) -> usize { self.calls.len() }
+    pub fn is_empty(&self) -> bool { self.calls.is_empty() }
+
+    /// Orden en que `flush` dibuja: los opacos en orden de envío y después los
+    /// transparentes del más lejano al más cercano
+    pub fn sorted(&self) -> Vec<&DrawCall<'a>> {
+        let (mut transparent, mut order): (Vec<_>, Vec<_>) =
+            self.calls.iter().partition(|c| c.state.is_transparent());
+        // A igual profundidad (capas concéntricas: nubes, atmósfera) primero la más chica;
+        // si también empatan, el orden estable respeta el orden de envío
+        transparent.sort_by(|a, b| a.view_depth().total_cmp(&b.view_depth()).then(a.scale().total_cmp(&b.scale())));
+        order.extend(transparent);
+        order
+    }
+
+    /// Dibuja todo y vacía la cola
+    pub fn flush(&mut self, fb: &mut Framebuffer, viewport: Mat4) {
+        for c in self.sorted() {
+            draw_mesh(fb, c.mesh, c.shader, &c.uniforms, viewport, &c.state);
+        }
+        self.calls.clear();
+    }
+}
@@ -1,12 +1,78 @@
 use crate::math::{Vec2, Vec3, Vec4};
 
-use super::buffers::{Framebuffer, Color};
+use super::buffers::Tile;
+use super::state::RenderState;
+
+/// Cantidad de varyings propios (escalares) que un shader puede pasar del vertex al fragment
+pub const MAX_VARYINGS: usize = 8;
+
+/// Calificador de interpolación de un varying propio (como en GLSL)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Interp {
+    /// Corregido por perspectiva (`smooth`)
+    #[default]
+    Perspective,
+    /// Lineal en pantalla
+    NoPerspective,
+    /// Sin interpolar: vale lo del primer vértice del triángulo (vértice provocador)
+    Flat,
+}
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Varyings {
     pub pos_ws: Vec3,
     pub nrm_ws: Vec3,
     pub uv: Vec2,
+    /// Varyings propios del shader; `Shader::varyings` dice cuántos usa y cómo se interpolan
+    pub custom: [f32; MAX_VARYINGS],
+    /// Derivadas en pantalla (por pixel en x / en y) dentro del quad 2x2.
+    /// Sólo las llena el rasterizador; en los vértices valen cero.
+    pub dfdx: Deltas,
+    pub dfdy: Deltas,
+}
+
+/// Diferencia de los varyings entre dos pixeles vecinos
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Deltas {
+    pub pos_ws: Vec3,
+    pub nrm_ws: Vec3,
+    pub uv: Vec2,
+    pub custom: [f32; MAX_VARYINGS],
+}
+
+impl Deltas {
+    /// Cuánto avanza `pos_ws` entre pixeles (unidades de mundo por pixel)
+    #[inline]
+    pub fn pos_step(&self) -> f32 { self.pos_ws.length() }
+
+    /// Cuánto avanza `uv` entre pixeles
+    #[inline]
+    pub fn uv_step(&self) -> f32 { self.uv.length() }
+}
+
+impl Varyings {
+    /// Ancho del pixel en el mundo: el mayor paso de `pos_ws` en x o en y
+    #[inline]
+    pub fn footprint(&self) -> f32 { self.dfdx.pos_step().max(self.dfdy.pos_step()) }
+
+    /// Ancho del pixel en uv
+    #[inline]
+    pub fn uv_footprint(&self) -> f32 { self.dfdx.uv_step().max(self.dfdy.uv_step()) }
+
+    /// `self - from` (cero si algún valor no es finito, p.ej. un pixel ayudante degenerado)
+    fn delta(&self, from: &Varyings) -> Deltas {
+        let d = Deltas {
+            pos_ws: self.pos_ws - from.pos_ws,
+            nrm_ws: self.nrm_ws - from.nrm_ws,
+            uv: self.uv - from.uv,
+            custom: std::array::from_fn(|k| self.custom[k] - from.custom[k]),
+        };
+        let finite = [d.pos_ws.x, d.pos_ws.y, d.pos_ws.z, d.nrm_ws.x, d.nrm_ws.y, d.nrm_ws.z, d.uv.x, d.uv.y]
+            .iter()
+            .chain(&d.custom)
+            .all(|v| v.is_finite());
+        if finite { d } else { Deltas::default() }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -17,26 +83,55 @@ pub struct RasterInput {
     pub v: [Varyings; 3],   
 }
 
+/// Doble del área con signo del triángulo en pantalla (el `denom` de las barycentricas)
 #[inline(always)]
-fn barycentric(p0: (f32,f32), p1: (f32,f32), p2: (f32,f32), px: (f32,f32)) -> (f32,f32,f32,f32) {
-    let (x0,y0) = p0; let (x1,y1) = p1; let (x2,y2) = p2; let (x,y) = px;
-    let denom = (y1 - y2)*(x0 - x2) + (x2 - x1)*(y0 - y2);
-    let w0 = ((y1 - y2)*(x - x2) + (x2 - x1)*(y - y2)) / denom;
-    let w1 = ((y2 - y0)*(x - x2) + (x0 - x2)*(y - y2)) / denom;
-    let w2 = 1.0 - w0 - w1;
-    (w0,w1,w2,denom)
+pub fn signed_area(p0: (f32,f32), p1: (f32,f32), p2: (f32,f32)) -> f32 {
+    let (x0,y0) = p0; let (x1,y1) = p1; let (x2,y2) = p2;
+    (y1 - y2)*(x0 - x2) + (x2 - x1)*(y0 - y2)
 }
 
+/// Bits de subpixel de las coordenadas en punto fijo (1/256 de pixel)
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+
+/// Coordenada de pantalla -> punto fijo
 #[inline(always)]
-fn inside_triangle(w0: f32, w1: f32, w2: f32) -> bool {
-    w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0
+fn to_fixed(v: f32) -> i64 { (v * SUBPIXEL as f32).round() as i64 }
+
+/// Función de arista `E(x, y) = a*x + b*y + c` en punto fijo (exacta: sin errores de
+/// redondeo, dos triángulos que comparten una arista ven los mismos valores con signo opuesto)
+#[derive(Copy, Clone, Debug)]
+struct Edge {
+    a: i64,
+    b: i64,
+    c: i64,
+    /// 0 si la arista es superior o izquierda (incluye los puntos sobre ella), -1 si no
+    bias: i64,
+}
+
+impl Edge {
+    /// Arista `p -> q` de un triángulo orientado de modo que el interior quede positivo.
+    /// Pantalla con y hacia abajo: en esa orientación las aristas izquierdas bajan (dy > 0)
+    /// y las superiores son horizontales y van hacia la izquierda (dx < 0).
+    fn new(p: (i64, i64), q: (i64, i64)) -> Self {
+        let (dx, dy) = (q.0 - p.0, q.1 - p.1);
+        let (a, b) = (dy, -dx);
+        let top_left = dy > 0 || (dy == 0 && dx < 0);
+        Self { a, b, c: -a*p.0 - b*p.1, bias: if top_left { 0 } else { -1 } }
+    }
+
+    #[inline(always)]
+    fn eval(&self, x: i64, y: i64) -> i64 { self.a*x + self.b*y + self.c }
 }
 
-/// Interpolación en perspectiva de un atributo escalar
+/// Suma componente a componente de valores de las tres aristas
 #[inline(always)]
-fn persp_interp1(w: [f32;3], a: [f32;3]) -> f32 {
-    let denom = w[0] + w[1] + w[2];
-    (a[0]*w[0] + a[1]*w[1] + a[2]*w[2]) / denom
+fn add3(e: [i64; 3], d: [i64; 3]) -> [i64; 3] { [e[0] + d[0], e[1] + d[1], e[2] + d[2]] }
+
+/// Regla top-left: el punto está dentro si las tres aristas lo aceptan
+#[inline(always)]
+fn covers(edges: &[Edge; 3], e: [i64; 3]) -> bool {
+    e[0] + edges[0].bias >= 0 && e[1] + edges[1].bias >= 0 && e[2] + edges[2].bias >= 0
 }
 
 /// Interpolación en perspectiva de Vec2
@@ -58,50 +153,278 @@ fn persp_interp3(w: [f32;3], a: [Vec3;3]) -> Vec3 {
     Vec3::new(ax/denom, ay/denom, az/denom)
 }
 
-/// Rasteriza un triángulo usando barycentrics + zbuffer y llama a `shade_pixel(x,y, vary, z)`.
-pub fn raster_triangle<F: FnMut(i32, i32, f32, Varyings) -> Color>(
-    fb: &mut Framebuffer,
+/// Rango de filas `[min_y, max_y]` que cubre el bounding box del triángulo (sin recortar)
+#[inline]
+pub fn row_span(tri: &RasterInput) -> (i32, i32) {
+    let min_y = tri.p.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor() as i32;
+    let max_y = tri.p.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+    (min_y, max_y)
+}
+
+/// Máximo de muestras por pixel (`Msaa::X8`)
+const MAX_SAMPLES: usize = 8;
+
+/// Profundidad y varyings en un punto de barycentricas (lineales en pantalla) dadas
+#[inline(always)]
+fn interpolate(tri: &RasterInput, layout: &[Interp], w0: f32, w1: f32, w2: f32) -> (f32, Varyings) {
+    let w0p = w0 * tri.inv_w[0];
+    let w1p = w1 * tri.inv_w[1];
+    let w2p = w2 * tri.inv_w[2];
+    let z = w0*tri.z[0] + w1*tri.z[1] + w2*tri.z[2];
+    let pos_ws = persp_interp3([w0p,w1p,w2p], [tri.v[0].pos_ws, tri.v[1].pos_ws, tri.v[2].pos_ws]);
+    let mut nrm_ws = persp_interp3([w0p,w1p,w2p], [tri.v[0].nrm_ws, tri.v[1].nrm_ws, tri.v[2].nrm_ws]).normalize();
+    if !nrm_ws.length().is_finite() { nrm_ws = tri.v[0].nrm_ws; }
+    let uv = persp_interp2([w0p,w1p,w2p], [tri.v[0].uv, tri.v[1].uv, tri.v[2].uv]);
+
+    let mut custom = [0.0; MAX_VARYINGS];
+    let persp_sum = w0p + w1p + w2p;
+    for (k, (c, interp)) in custom.iter_mut().zip(layout).enumerate() {
+        let a = [tri.v[0].custom[k], tri.v[1].custom[k], tri.v[2].custom[k]];
+        *c = match interp {
+            Interp::Perspective => (a[0]*w0p + a[1]*w1p + a[2]*w2p) / persp_sum,
+            Interp::NoPerspective => a[0]*w0 + a[1]*w1 + a[2]*w2,
+            Interp::Flat => a[0],
+        };
+    }
+    (z, Varyings { pos_ws, nrm_ws, uv, custom, ..Default::default() })
+}
+
+/// Rasteriza un triángulo dentro de `tile` usando funciones de arista + zbuffer y llama a `shade_pixel(x,y, vary, z)`.
+///
+/// Los vértices se llevan a punto fijo (1/256 de pixel) y las funciones de arista se evalúan
+/// con enteros, avanzando de forma incremental por el bounding box. Los puntos que caen justo
+/// sobre una arista se asignan con la regla top-left, así en una malla cerrada cada pixel
+/// (y cada muestra MSAA) lo cubre exactamente un triángulo: sin grietas ni pixeles dobles.
+///
+/// De los varyings propios sólo se interpolan los `layout.len()` primeros, cada uno según su `Interp`.
+///
+/// Se recorre en quads 2x2 alineados a coordenadas pares: los cuatro pixeles del quad se
+/// interpolan aunque alguno quede fuera del triángulo (se extrapola, como los pixeles
+/// "ayudantes" de una GPU) y sus diferencias dan `dfdx`/`dfdy`. Sólo se sombrean los que tienen
+/// alguna muestra cubierta; con MSAA el color se escribe en las muestras cubiertas.
+pub fn raster_triangle<F: FnMut(i32, i32, f32, Varyings) -> Vec4>(
+    tile: &mut Tile,
     tri: &RasterInput,
+    layout: &[Interp],
+    state: &RenderState,
     mut shade_pixel: F,
 ) {
-    if fb.width == 0 || fb.height == 0 {
+    if tile.width == 0 || tile.rows == 0 {
         return;
     }
 
-    // Bounding box 
-    let fb_width = fb.width as i32;
-    let fb_height = fb.height as i32;
-    
+    // Bounding box recortado al tile
+    let tile_y0 = tile.y0 as i32;
+    let tile_y1 = (tile.y0 + tile.rows) as i32 - 1;
+    let (span_y0, span_y1) = row_span(tri);
+
     let min_x = tri.p.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
-    let max_x = tri.p.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil().min((fb_width - 1) as f32) as i32;
-    let min_y = tri.p.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
-    let max_y = tri.p.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil().min((fb_height - 1) as f32) as i32;
+    let max_x = tri.p.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil().min((tile.width - 1) as f32) as i32;
+    let min_y = span_y0.max(tile_y0);
+    let max_y = span_y1.min(tile_y1);
 
     if min_x > max_x || min_y > max_y {
         return;
     }
 
-    let p0 = (tri.p[0].x, tri.p[0].y);
-    let p1 = (tri.p[1].x, tri.p[1].y);
-    let p2 = (tri.p[2].x, tri.p[2].y);
-
-    for y in min_y..=max_y {
-        for x in min_x..=max_x {
-            let (w0,w1,w2,_) = barycentric(p0,p1,p2, (x as f32 + 0.5, y as f32 + 0.5));
-            if !inside_triangle(w0,w1,w2) { continue; }
-
-            let w0p = w0 * tri.inv_w[0];
-            let w1p = w1 * tri.inv_w[1];
-            let w2p = w2 * tri.inv_w[2];
-            let z = w0*tri.z[0] + w1*tri.z[1] + w2*tri.z[2];
-            let pos_ws = persp_interp3([w0p,w1p,w2p], [tri.v[0].pos_ws, tri.v[1].pos_ws, tri.v[2].pos_ws]);
-            let mut nrm_ws = persp_interp3([w0p,w1p,w2p], [tri.v[0].nrm_ws, tri.v[1].nrm_ws, tri.v[2].nrm_ws]).normalize();
-            if !nrm_ws.length().is_finite() { nrm_ws = tri.v[0].nrm_ws; }
-            
-            let uv = persp_interp2([w0p,w1p,w2p], [tri.v[0].uv, tri.v[1].uv, tri.v[2].uv]);
-            let vary = Varyings { pos_ws, nrm_ws, uv };
-            let col = shade_pixel(x, y, z, vary);
-            fb.put_pixel(x, y, z, col);
+    // Vértices en punto fijo; si el triángulo viene en sentido horario se recorre 0-2-1
+    // para que el interior quede positivo (el peso de cada vértice sigue yendo a su índice)
+    let fp = tri.p.map(|p| (to_fixed(p.x), to_fixed(p.y)));
+    let area = Edge::new(fp[0], fp[1]).eval(fp[2].0, fp[2].1);
+    if area == 0 { return; }
+    let (i1, i2) = if area > 0 { (1, 2) } else { (2, 1) };
+    // edges[k] vale 0 sobre la arista opuesta al vértice k
+    let mut edges = [Edge::new(fp[0], fp[0]); 3];
+    edges[0] = Edge::new(fp[i1], fp[i2]);
+    edges[i1] = Edge::new(fp[i2], fp[0]);
+    edges[i2] = Edge::new(fp[0], fp[i1]);
+    let inv_area = 1.0 / area.abs() as f32;
+    let weights = |e: [i64; 3]| (e[0] as f32 * inv_area, e[1] as f32 * inv_area, e[2] as f32 * inv_area);
+
+    // Pasos de un pixel en x / en y, y desplazamiento de cada muestra respecto al centro
+    let step_x = edges.map(|e| e.a * SUBPIXEL);
+    let step_y = edges.map(|e| e.b * SUBPIXEL);
+    let offsets = tile.msaa.positions();
+    let mut sample_off = [[0i64; 3]; MAX_SAMPLES];
+    for (o, &(dx, dy)) in sample_off.iter_mut().zip(offsets) {
+        *o = edges.map(|e| e.a * to_fixed(dx) + e.b * to_fixed(dy));
+    }
+
+    // Alineado a pares absolutos (los tiles empiezan en múltiplos de TILE_ROWS, que es par),
+    // así el resultado no depende de cómo se reparten las filas entre hilos
+    let (qx0, qy0) = (min_x & !1, min_y & !1);
+    let center = |v: i32| v as i64 * SUBPIXEL + SUBPIXEL / 2;
+    let mut row = edges.map(|e| e.eval(center(qx0), center(qy0)));
+    for qy in (qy0..=max_y).step_by(2) {
+        let mut eq = row;
+        for qx in (qx0..=max_x).step_by(2) {
+            // Orden: (0,0) (1,0) (0,1) (1,1)
+            let px = [(qx, qy), (qx + 1, qy), (qx, qy + 1), (qx + 1, qy + 1)];
+            let e_px = [eq, add3(eq, step_x), add3(eq, step_y), add3(add3(eq, step_x), step_y)];
+            eq = add3(add3(eq, step_x), step_x);
+
+            // Cobertura y profundidad por muestra (sin MSAA, una sola muestra en el centro)
+            let mut mask = [0u32; 4];
+            let mut zs = [[0.0f32; MAX_SAMPLES]; 4];
+            for i in 0..4 {
+                for (s, off) in sample_off.iter().enumerate().take(offsets.len()) {
+                    let e = add3(e_px[i], *off);
+                    if covers(&edges, e) {
+                        let (w0, w1, w2) = weights(e);
+                        mask[i] |= 1 << s;
+                        zs[i][s] = w0*tri.z[0] + w1*tri.z[1] + w2*tri.z[2];
+                    }
+                }
+            }
+            if mask == [0; 4] { continue; }
+
+            // Varyings en el centro de cada pixel (se sombrea una vez por pixel)
+            let vals = e_px.map(|e| {
+                let (w0, w1, w2) = weights(e);
+                interpolate(tri, layout, w0, w1, w2)
+            });
+            for (i, &(x, y)) in px.iter().enumerate() {
+                if mask[i] == 0 || x > max_x || y < min_y || y > max_y { continue; }
+                let (row, col) = (i & 2, i & 1);
+                let (z, mut vary) = vals[i];
+                vary.dfdx = vals[row + 1].1.delta(&vals[row].1);
+                vary.dfdy = vals[col + 2].1.delta(&vals[col].1);
+                let c = shade_pixel(x, y, z, vary);
+                tile.put_samples(x, y, mask[i], &zs[i], c, state);
+            }
         }
+        row = add3(add3(row, step_y), step_y);
     }
-}
\ No newline at end of file
+}
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::buffers::{Framebuffer, Msaa};
+
+    #[test]
+    fn quad_derivatives_are_screen_steps() {
+        // uv lineal en pantalla (w = 1): u = x/10, v = y/20
+        let vert = |x: f32, y: f32| Varyings { uv: Vec2::new(x / 10.0, y / 20.0), ..Default::default() };
+        let tri = RasterInput {
+            p: [Vec4::new(1.3, 0.7, 0.5, 1.0), Vec4::new(30.2, 4.1, 0.5, 1.0), Vec4::new(7.9, 25.6, 0.5, 1.0)],
+            z: [0.5; 3],
+            inv_w: [1.0; 3],
+            v: [vert(1.3, 0.7), vert(30.2, 4.1), vert(7.9, 25.6)],
+        };
+        let mut fb = Framebuffer::new(32, 32);
+        let mut shaded = 0;
+        raster_triangle(&mut fb.full_tile(), &tri, &[], &RenderState::default(), |_, _, _, v| {
+            // También en los bordes, donde parte del quad cae fuera del triángulo
+            assert!((v.dfdx.uv.x - 0.1).abs() < 1e-5 && v.dfdx.uv.y.abs() < 1e-5);
+            assert!(v.dfdy.uv.x.abs() < 1e-5 && (v.dfdy.uv.y - 0.05).abs() < 1e-5);
+            shaded += 1;
+            Vec4::new(1.0, 1.0, 1.0, 1.0)
+        });
+        assert!(shaded > 100);
+    }
+
+    #[test]
+    fn interpolation_qualifiers() {
+        // v1 está 4 veces más lejos (w = 4): perspectiva y pantalla dan valores distintos
+        let vert = |c: [f32; 3]| {
+            let mut v = Varyings::default();
+            v.custom[..3].copy_from_slice(&c);
+            v
+        };
+        let tri = RasterInput {
+            p: [Vec4::new(0.0, 0.0, 0.5, 1.0), Vec4::new(16.0, 0.0, 0.5, 1.0), Vec4::new(0.0, 16.0, 0.5, 1.0)],
+            z: [0.5; 3],
+            inv_w: [1.0, 0.25, 1.0],
+            v: [vert([0.0, 0.0, 7.0]), vert([1.0, 1.0, 8.0]), vert([0.0, 0.0, 9.0])],
+        };
+        let layout = [Interp::Perspective, Interp::NoPerspective, Interp::Flat];
+        let mut fb = Framebuffer::new(16, 16);
+        let mut seen = false;
+        raster_triangle(&mut fb.full_tile(), &tri, &layout, &RenderState::default(), |x, y, _, v| {
+            if (x, y) == (7, 3) {
+                let (w1, w2) = (7.5 / 16.0, 3.5 / 16.0);
+                let w0 = 1.0 - w1 - w2;
+                assert!((v.custom[0] - w1 * 0.25 / (w0 + w1 * 0.25 + w2)).abs() < 1e-5);
+                assert!((v.custom[1] - w1).abs() < 1e-5);
+                assert_eq!(v.custom[2], 7.0);
+                // Los slots fuera del layout no se interpolan
+                assert_eq!(v.custom[3], 0.0);
+                seen = true;
+            }
+            Vec4::new(1.0, 1.0, 1.0, 1.0)
+        });
+        assert!(seen);
+    }
+
+    #[test]
+    fn tessellated_quad_covers_each_sample_once() {
+        // Cuadro [2.5, 18.5) x [3.5, 19.5) partido en 4x4 celdas de 4 px, con los vértices
+        // del borde sobre centros de pixel (empates en todas las aristas) y los interiores
+        // movidos un poco; diagonales y orientación alternadas
+        let n = 4;
+        let vert = |i: usize, j: usize| {
+            let (mut x, mut y) = (2.5 + 4.0 * i as f32, 3.5 + 4.0 * j as f32);
+            if i > 0 && i < n && j > 0 && j < n {
+                x += [0.0, 0.37, -1.0 / 256.0, -0.61][(i + j) % 4];
+                y += [0.0, -0.29, 0.5, 1.0 / 3.0][(i * 3 + j) % 4];
+            }
+            Vec4::new(x, y, 0.5, 1.0)
+        };
+        let mut tris = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let (a, b, c, d) = (vert(i, j), vert(i + 1, j), vert(i + 1, j + 1), vert(i, j + 1));
+                let halves = if (i + j) % 2 == 0 { [[a, b, c], [a, c, d]] } else { [[a, b, d], [b, c, d]] };
+                for (k, mut p) in halves.into_iter().enumerate() {
+                    if k == 1 { p.swap(1, 2); }
+                    tris.push(RasterInput { p, z: [0.5; 3], inv_w: [1.0; 3], v: [Varyings::default(); 3] });
+                }
+            }
+        }
+
+        // Suma aditiva sin escribir profundidad: cada muestra termina con su número de coberturas
+        let state = RenderState::transparent(crate::renderer::state::BlendMode::Additive);
+        for msaa in [Msaa::Off, Msaa::X4, Msaa::X8] {
+            let (w, h) = (24, 24);
+            let mut fb = Framebuffer::with_msaa(w, h, msaa);
+            for t in &tris {
+                raster_triangle(&mut fb.full_tile(), t, &[], &state, |_, _, _, _| Vec4::new(1.0, 1.0, 1.0, 1.0));
+            }
+            let counts = if msaa == Msaa::Off { &fb.hdr } else { &fb.samples };
+            let pos = msaa.positions();
+            for y in 0..h {
+                for x in 0..w {
+                    for (s, &(dx, dy)) in pos.iter().enumerate() {
+                        let (sx, sy) = (x as f32 + 0.5 + dx, y as f32 + 0.5 + dy);
+                        let inside = (2.5..18.5).contains(&sx) && (3.5..19.5).contains(&sy);
+                        let got = counts[(y*w + x) * pos.len() + s].x;
+                        assert_eq!(got, inside as u32 as f32, "{:?} pixel ({}, {}) muestra {}", msaa, x, y, s);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn msaa_coverage_is_fractional_on_edges() {
+        // Media pantalla cubierta por la diagonal y = x de un cuadro 8x8
+        let vert = Varyings::default();
+        let tri = RasterInput {
+            p: [Vec4::new(0.0, 0.0, 0.5, 1.0), Vec4::new(8.0, 8.0, 0.5, 1.0), Vec4::new(8.0, 0.0, 0.5, 1.0)],
+            z: [0.5; 3],
+            inv_w: [1.0; 3],
+            v: [vert; 3],
+        };
+        let mut fb = Framebuffer::with_msaa(8, 8, Msaa::X4);
+        fb.clear_color(crate::renderer::buffers::Color::rgb(0, 0, 0));
+        raster_triangle(&mut fb.full_tile(), &tri, &[], &RenderState::default(), |_, _, _, _| Vec4::new(1.0, 1.0, 1.0, 1.0));
+        fb.resolve_samples();
+        // Lejos del borde: todo o nada; sobre la diagonal: 2 de 4 muestras
+        assert_eq!(fb.hdr[2*8 + 6].x, 1.0);
+        assert_eq!(fb.hdr[6*8 + 2].x, 0.0);
+        for i in 0..8 {
+            assert_eq!(fb.hdr[i*8 + i].x, 0.5);
+        }
+    }
+}
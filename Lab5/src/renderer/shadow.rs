@@ -0,0 +1,154 @@
+use crate::math::{Mat4, Vec3, Vec4, look_at_rh, perspective_rh, viewport};
+use crate::math::mat::ortho_rh;
+use super::buffers::Framebuffer;
+
+/// Una vista de la luz (una cara del cubo en las luces puntuales) con su profundidad
+struct Face {
+    view: Mat4,
+    proj: Mat4,
+    view_proj: Mat4,
+    depth: Vec<f32>,
+}
+
+/// Mapa de sombras de la luz principal: la profundidad de la escena vista desde la luz.
+/// Luz puntual: cubo de 6 caras de 90°; direccional: una proyección ortográfica que
+/// encuadra la escena. `size` = 0 lo desactiva.
+pub struct ShadowMap {
+    size: usize,
+    faces: Vec<Face>,
+    point: Option<Vec3>,
+    texel: f32,      // tamaño de un texel: a distancia 1 de la luz (puntual) o en el mundo (direccional)
+    fb: Framebuffer, // sólo profundidad, reutilizado por cada cara
+}
+
+impl std::fmt::Debug for ShadowMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShadowMap")
+            .field("size", &self.size)
+            .field("faces", &self.faces.len())
+            .field("point", &self.point)
+            .finish()
+    }
+}
+
+impl ShadowMap {
+    pub fn new(size: usize) -> Self {
+        Self { size, faces: Vec::new(), point: None, texel: 0.0, fb: Framebuffer::depth_only(size, size) }
+    }
+
+    pub fn size(&self) -> usize { self.size }
+    pub fn is_enabled(&self) -> bool { self.size > 0 }
+
+    fn set_faces(&mut self, views: impl Iterator<Item = Mat4>, proj: Mat4) {
+        let n = self.size * self.size;
+        self.faces = views
+            .map(|view| Face { view, proj, view_proj: proj * view, depth: vec![f32::INFINITY; n] })
+            .collect();
+    }
+
+    /// Luz puntual en `pos`: las 6 caras del cubo entre `near` y `far`
+    pub fn set_point(&mut self, pos: Vec3, near: f32, far: f32) {
+        let axes = [
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            (Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+        ];
+        let proj = perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, near, far);
+        self.set_faces(axes.iter().map(|&(d, up)| look_at_rh(pos, pos + d, up)), proj);
+        self.point = Some(pos);
+        self.texel = 2.0 / self.size.max(1) as f32;
+    }
+
+    /// Luz direccional (`to_light` apunta hacia la luz) sobre la esfera `center`/`radius`
+    pub fn set_directional(&mut self, to_light: Vec3, center: Vec3, radius: f32) {
+        let d = to_light.normalize();
+        let up = if d.y.abs() > 0.99 { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let view = look_at_rh(center + d * (2.0 * radius), center, up);
+        let proj = ortho_rh(-radius, radius, -radius, radius, 0.0, 4.0 * radius);
+        self.set_faces(std::iter::once(view), proj);
+        self.point = None;
+        self.texel = 2.0 * radius / self.size.max(1) as f32;
+    }
+
+    /// Dibuja cada cara: `draw(fb, view, proj, viewport)` debe rasterizar los que
+    /// proyectan sombra en `fb` (sólo profundidad)
+    pub fn render(&mut self, mut draw: impl FnMut(&mut Framebuffer, Mat4, Mat4, Mat4)) {
+        let vp = viewport(0.0, 0.0, self.size as f32, self.size as f32, 1.0);
+        for face in &mut self.faces {
+            self.fb.clear_depth();
+            draw(&mut self.fb, face.view, face.proj, vp);
+            face.depth.copy_from_slice(&self.fb.depth);
+        }
+    }
+
+    /// Fracción de luz (0 = sombra, 1 = iluminado) que llega a `pos` con normal `nrm`,
+    /// con PCF 3x3. El punto se corre un poco a lo largo de la normal (un texel y medio)
+    /// para evitar el "acne" de la propia superficie.
+    pub fn visibility(&self, pos: Vec3, nrm: Vec3) -> f32 {
+        if self.faces.is_empty() { return 1.0; }
+        let texel_ws = match self.point {
+            Some(l) => (pos - l).length() * self.texel,
+            None => self.texel,
+        };
+        let q = pos + nrm.normalize() * (1.5 * texel_ws);
+        let size = self.size as i32;
+
+        for face in &self.faces {
+            let c = face.view_proj * Vec4::from3(q, 1.0);
+            if c.w <= 0.0 { continue; }
+            let (x, y, z) = (c.x / c.w, c.y / c.w, c.z / c.w);
+            if x.abs() > 1.0 || y.abs() > 1.0 { continue; }
+
+            // Mismo mapeo que `viewport` (y hacia abajo, z en [0,1])
+            let sx = ((x + 1.0) * 0.5 * self.size as f32) as i32;
+            let sy = ((1.0 - y) * 0.5 * self.size as f32) as i32;
+            let depth = (z + 1.0) * 0.5;
+            let mut lit = 0;
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (tx, ty) = ((sx + dx).clamp(0, size - 1), (sy + dy).clamp(0, size - 1));
+                    if depth <= face.depth[(ty * size + tx) as usize] + 1e-5 { lit += 1; }
+                }
+            }
+            return lit as f32 / 9.0;
+        }
+        1.0
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::mat::{scale, translate};
+    use crate::renderer::uniforms::test_uniforms;
+    use crate::renderer::pipeline::draw_mesh;
+    use crate::renderer::state::RenderState;
+    use crate::scene::load_obj;
+    use crate::shaders::moon_vs::Moon;
+
+    #[test]
+    fn occluder_casts_shadow() {
+        // Luz puntual en el origen y una esfera de radio 0.5 en x = 3
+        let mesh = load_obj("assets/sphere.obj").unwrap();
+        let mut map = ShadowMap::new(128);
+        map.set_point(Vec3::ZERO, 0.1, 20.0);
+        let mut u = test_uniforms(16.0 / 9.0);
+        u.model = translate(Vec3::new(3.0, 0.0, 0.0)) * scale(Vec3::new(0.5, 0.5, 0.5));
+        map.render(|fb, view, proj, vp| {
+            let u = crate::renderer::Uniforms { view, proj, ..u };
+            draw_mesh(fb, &mesh, &Moon, &u, vp, &RenderState::shadow_caster());
+        });
+
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        // Detrás de la esfera: sombra; a un costado o delante: luz
+        assert_eq!(map.visibility(Vec3::new(6.0, 0.0, 0.0), -Vec3::new(1.0, 0.0, 0.0)), 0.0);
+        assert_eq!(map.visibility(Vec3::new(6.0, 3.0, 0.0), up), 1.0);
+        assert_eq!(map.visibility(Vec3::new(2.0, 0.0, 0.0), -Vec3::new(1.0, 0.0, 0.0)), 1.0);
+        // El lado iluminado de la propia esfera no se sombrea a sí mismo
+        assert_eq!(map.visibility(Vec3::new(2.5, 0.0, 0.0), -Vec3::new(1.0, 0.0, 0.0)), 1.0);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::math::Vec4;
+
+/// Qué caras descartar antes de rasterizar
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CullMode {
+    #[default]
+    None,
+    Back,
+    Front,
+}
+
+/// Orden de vértices que define la cara frontal (visto en NDC, y hacia arriba)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FrontFace {
+    #[default]
+    Ccw,
+    Cw,
+}
+
+/// Cómo combinar el color del fragmento (src) con el del framebuffer (dst)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Sobrescribe: dst = src
+    #[default]
+    None,
+    /// dst = src*a + dst*(1-a)
+    Alpha,
+    /// dst = dst + src*a
+    Additive,
+    /// src ya viene multiplicado por alpha: dst = src + dst*(1-a)
+    Premultiplied,
+}
+
+impl BlendMode {
+    /// Mezcla en radiancia lineal (sin recortar: el framebuffer es HDR)
+    #[inline]
+    pub fn blend(self, src: Vec4, dst: Vec4) -> Vec4 {
+        let a = src.w.clamp(0.0, 1.0);
+        let rgb = match self {
+            BlendMode::None => return src,
+            BlendMode::Alpha => src.xyz() * a + dst.xyz() * (1.0 - a),
+            BlendMode::Additive => dst.xyz() + src.xyz() * a,
+            BlendMode::Premultiplied => src.xyz() + dst.xyz() * (1.0 - a),
+        };
+        Vec4::from3(rgb, (a + dst.w * (1.0 - a)).min(1.0))
+    }
+}
+
+/// Estado fijo del pipeline para un `draw_mesh`
+#[derive(Copy, Clone, Debug)]
+pub struct RenderState {
+    pub cull: CullMode,
+    pub front_face: FrontFace,
+    pub blend: BlendMode,
+    pub depth_write: bool,
+    pub color_write: bool, // false: sólo profundidad, sin evaluar el fragment
+    pub threads: usize, // hilos de raster/fragment (0 = automático)
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            cull: CullMode::None,
+            front_face: FrontFace::Ccw,
+            blend: BlendMode::None,
+            depth_write: true,
+            color_write: true,
+            threads: 0,
+        }
+    }
+}
+
+impl RenderState {
+    /// Sólidos cerrados: descarta caras traseras
+    pub fn opaque() -> Self {
+        Self { cull: CullMode::Back, ..Default::default() }
+    }
+
+    /// Capas semitransparentes: mezcla alpha sin escribir profundidad
+    pub fn transparent(blend: BlendMode) -> Self {
+        Self { blend, depth_write: false, ..Default::default() }
+    }
+
+    /// Pasada de sombras: sólo escribe profundidad, sin culling (las mallas del
+    /// mapa se ven desde la luz y cualquier cara puede tapar)
+    pub fn shadow_caster() -> Self {
+        Self { color_write: false, ..Default::default() }
+    }
+
+    /// Los draws con mezcla van a la pasada transparente (ordenada de atrás hacia adelante)
+    pub fn is_transparent(&self) -> bool { self.blend != BlendMode::None }
+
+    /// Hilos efectivos para rasterizar (`threads == 0` usa todos los núcleos)
+    pub fn thread_count(&self) -> usize {
+        if self.threads > 0 { return self.threads; }
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    }
+
+    /// Decide si descartar un triángulo a partir de su área con signo en pantalla.
+    /// El viewport invierte y, así que un triángulo CCW en NDC tiene área negativa en pantalla.
+    #[inline]
+    pub fn culls(&self, screen_area: f32) -> bool {
+        if screen_area == 0.0 || !screen_area.is_finite() { return true; }
+        let ccw = screen_area < 0.0;
+        let front = match self.front_face {
+            FrontFace::Ccw => ccw,
+            FrontFace::Cw => !ccw,
+        };
+        match self.cull {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        }
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn culling_by_mode_and_winding() {
+        // Área en pantalla: negativa para un triángulo CCW en NDC (el viewport invierte y)
+        let (ccw, cw) = (-2.0, 2.0);
+        let culls = |cull, front_face, area| RenderState { cull, front_face, ..Default::default() }.culls(area);
+        for front_face in [FrontFace::Ccw, FrontFace::Cw] {
+            let (front, back) = if front_face == FrontFace::Ccw { (ccw, cw) } else { (cw, ccw) };
+            assert!(!culls(CullMode::None, front_face, front) && !culls(CullMode::None, front_face, back));
+            assert!(!culls(CullMode::Back, front_face, front) && culls(CullMode::Back, front_face, back));
+            assert!(culls(CullMode::Front, front_face, front) && !culls(CullMode::Front, front_face, back));
+            // Sin área (o no finita) no hay nada que dibujar, con cualquier modo
+            for cull in [CullMode::None, CullMode::Back, CullMode::Front] {
+                for area in [0.0, f32::NAN, f32::INFINITY] {
+                    assert!(culls(cull, front_face, area), "{:?} {:?} {}", cull, front_face, area);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blend_equations() {
+        let dst = Vec4::new(0.2, 0.4, 0.6, 1.0);
+        let src = Vec4::new(1.0, 0.5, 2.0, 0.25);
+        let close = |a: Vec4, b: Vec4| assert!((a.xyz() - b.xyz()).length() + (a.w - b.w).abs() < 1e-6, "{:?} != {:?}", a, b);
+        close(BlendMode::None.blend(src, dst), src);
+        close(BlendMode::Alpha.blend(src, dst), Vec4::new(0.4, 0.425, 0.95, 1.0));
+        close(BlendMode::Additive.blend(src, dst), Vec4::new(0.45, 0.525, 1.1, 1.0));
+        close(BlendMode::Premultiplied.blend(src, dst), Vec4::new(1.15, 0.8, 2.45, 1.0));
+        // Alpha resultante sobre un fondo transparente
+        close(BlendMode::Alpha.blend(src, Vec4::new(0.0, 0.0, 0.0, 0.0)), Vec4::new(0.25, 0.125, 0.5, 0.25));
+    }
+
+    #[test]
+    fn transparent_state_tests_depth_without_writing_it() {
+        use crate::renderer::Framebuffer;
+        let mut fb = Framebuffer::new(1, 1);
+        let c = Vec4::new(1.0, 0.0, 0.0, 0.5);
+        let state = RenderState::transparent(BlendMode::Alpha);
+        fb.full_tile().put_samples(0, 0, 1, &[0.5], c, &state);
+        assert_eq!(fb.depth[0], f32::INFINITY);
+        let mixed = fb.hdr[0];
+        // Más lejos que algo opaco ya dibujado: se descarta
+        fb.full_tile().put_samples(0, 0, 1, &[0.3], Vec4::new(0.0, 0.0, 1.0, 1.0), &RenderState::opaque());
+        fb.full_tile().put_samples(0, 0, 1, &[0.4], c, &state);
+        assert_eq!((fb.depth[0], fb.hdr[0].z), (0.3, 1.0));
+        assert!(mixed.x > 0.0);
+    }
+}
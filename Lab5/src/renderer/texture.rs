@@ -0,0 +1,257 @@
+use std::ops::{Add, Mul};
+use crate::math::{Vec2, Vec4};
+use super::tonemap::srgb_to_linear;
+
+/// Imagen `w`x`h` a mitad de resolución (mínimo 1x1) promediando bloques de 2x2. Con un
+/// lado impar el último bloque se estira a 3 pixeles, así no se pierde ninguna fila ni columna.
+/// La comparten los mips de `Texture2D` y la pirámide del bloom.
+pub fn downsample_2x2<T>(w: usize, h: usize, px: &[T]) -> (usize, usize, Vec<T>)
+where T: Copy + Add<Output = T> + Mul<f32, Output = T>
+{
+    let (w2, h2) = ((w / 2).max(1), (h / 2).max(1));
+    // Rango de la imagen original que cubre el pixel `i` de `n` (el último toma el resto)
+    let span = |i: usize, n: usize, full: usize| 2*i..if i + 1 == n { full } else { 2*i + 2 };
+    let mut out = Vec::with_capacity(w2*h2);
+    for y in 0..h2 {
+        for x in 0..w2 {
+            let (xs, ys) = (span(x, w2, w), span(y, h2, h));
+            let k = 1.0 / (xs.len() * ys.len()) as f32;
+            let mut cells = ys.flat_map(|yy| xs.clone().map(move |xx| px[yy*w + xx]));
+            let first = cells.next().unwrap();
+            out.push(cells.fold(first, |sum, c| sum + c) * k);
+        }
+    }
+    (w2, h2, out)
+}
+
+/// Filtrado de muestreo
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    /// Texel más cercano del mip más cercano
+    Nearest,
+    /// Bilineal en el mip más cercano
+    #[default]
+    Bilinear,
+    /// Bilineal en los dos mips vecinos y mezcla entre ellos
+    Trilinear,
+}
+
+/// Qué hacer con coordenadas fuera de [0,1]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl Wrap {
+    /// Aplica el modo a un índice de texel en [0, n)
+    #[inline]
+    fn apply(self, i: i32, n: i32) -> usize {
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Clamp => i.clamp(0, n - 1),
+            Wrap::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                if m < n { m } else { 2 * n - 1 - m }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Estado de muestreo (independiente de la textura, como un sampler de GPU)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl Sampler {
+    pub const fn new(filter: Filter, wrap: Wrap) -> Self {
+        Self { filter, wrap_u: wrap, wrap_v: wrap }
+    }
+}
+
+/// Un nivel de la cadena de mips
+#[derive(Clone, Debug)]
+struct Level {
+    w: usize,
+    h: usize,
+    texels: Vec<Vec4>,
+}
+
+impl Level {
+    #[inline]
+    fn texel(&self, x: i32, y: i32, s: &Sampler) -> Vec4 {
+        let x = s.wrap_u.apply(x, self.w as i32);
+        let y = s.wrap_v.apply(y, self.h as i32);
+        self.texels[y*self.w + x]
+    }
+
+    fn nearest(&self, uv: Vec2, s: &Sampler) -> Vec4 {
+        let x = (uv.x * self.w as f32).floor() as i32;
+        let y = (uv.y * self.h as f32).floor() as i32;
+        self.texel(x, y, s)
+    }
+
+    fn bilinear(&self, uv: Vec2, s: &Sampler) -> Vec4 {
+        let fx = uv.x * self.w as f32 - 0.5;
+        let fy = uv.y * self.h as f32 - 0.5;
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let a = self.texel(x0, y0, s) * (1.0 - tx) + self.texel(x0 + 1, y0, s) * tx;
+        let b = self.texel(x0, y0 + 1, s) * (1.0 - tx) + self.texel(x0 + 1, y0 + 1, s) * tx;
+        a * (1.0 - ty) + b * ty
+    }
+
+    /// Siguiente mip (`downsample_2x2`)
+    fn downsample(&self) -> Level {
+        let (w, h, texels) = downsample_2x2(self.w, self.h, &self.texels);
+        Level { w, h, texels }
+    }
+}
+
+/// Textura RGBA en flotante lineal con cadena de mips completa (hasta 1x1).
+/// `uv` sigue la convención de los `.obj`: v = 0 abajo, v = 1 arriba de la imagen.
+#[derive(Clone, Debug)]
+pub struct Texture2D {
+    levels: Vec<Level>,
+}
+
+impl Texture2D {
+    /// Crea la textura (texels lineales, fila 0 arriba) y genera los mips
+    pub fn from_texels(width: usize, height: usize, texels: Vec<Vec4>) -> Self {
+        assert!(width > 0 && height > 0 && texels.len() == width*height, "tamaño de textura inválido");
+        let mut levels = vec![Level { w: width, h: height, texels }];
+        loop {
+            let last = levels.last().unwrap();
+            if last.w == 1 && last.h == 1 { break; }
+            let next = last.downsample();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Carga una imagen. Con `srgb` se decodifica a lineal (albedo/color);
+    /// sin él los valores se usan tal cual (opacidad, máscaras, alturas).
+    pub fn load(path: &str, srgb: bool) -> Result<Self, String> {
+        let img = image::open(path)
+            .map_err(|e| format!("No pude abrir la textura '{}': {}", path, e))?
+            .to_rgba8();
+        let (w, h) = img.dimensions();
+        let decode = |c: u8| {
+            let c = c as f32 / 255.0;
+            if srgb { srgb_to_linear(c) } else { c }
+        };
+        let texels = img.pixels()
+            .map(|p| Vec4::new(decode(p[0]), decode(p[1]), decode(p[2]), p[3] as f32 / 255.0))
+            .collect();
+        Ok(Self::from_texels(w as usize, h as usize, texels))
+    }
+
+    pub fn width(&self) -> usize { self.levels[0].w }
+    pub fn height(&self) -> usize { self.levels[0].h }
+    pub fn mip_levels(&self) -> usize { self.levels.len() }
+
+    /// Muestrea el nivel base (lod 0)
+    pub fn sample(&self, s: &Sampler, uv: Vec2) -> Vec4 {
+        self.sample_lod(s, uv, 0.0)
+    }
+
+    /// Muestrea con un nivel de detalle explícito (0 = resolución completa)
+    pub fn sample_lod(&self, s: &Sampler, uv: Vec2, lod: f32) -> Vec4 {
+        let uv = Vec2::new(uv.x, 1.0 - uv.y);
+        let max = (self.levels.len() - 1) as f32;
+        let lod = lod.clamp(0.0, max);
+        match s.filter {
+            Filter::Nearest => self.levels[lod.round() as usize].nearest(uv, s),
+            Filter::Bilinear => self.levels[lod.round() as usize].bilinear(uv, s),
+            Filter::Trilinear => {
+                let l0 = lod.floor();
+                let t = lod - l0;
+                let a = self.levels[l0 as usize].bilinear(uv, s);
+                if t <= 0.0 { return a; }
+                let b = self.levels[(l0 as usize + 1).min(self.levels.len() - 1)].bilinear(uv, s);
+                a * (1.0 - t) + b * t
+            }
+        }
+    }
+
+    /// Muestrea eligiendo el mip a partir de las derivadas de `uv` en pantalla
+    pub fn sample_grad(&self, s: &Sampler, uv: Vec2, duv_dx: Vec2, duv_dy: Vec2) -> Vec4 {
+        self.sample_lod(s, uv, self.lod(duv_dx, duv_dy))
+    }
+
+    /// Nivel de detalle: log2 del mayor paso en texels por pixel
+    pub fn lod(&self, duv_dx: Vec2, duv_dy: Vec2) -> f32 {
+        let size = Vec2::new(self.width() as f32, self.height() as f32);
+        let dx = Vec2::new(duv_dx.x * size.x, duv_dx.y * size.y).length();
+        let dy = Vec2::new(duv_dy.x * size.x, duv_dy.y * size.y).length();
+        dx.max(dy).max(1e-8).log2()
+    }
+}
+
+/// Texturas enlazadas a un draw (lo que ve el fragment en `Uniforms::textures`)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TextureSet<'a> {
+    pub albedo: Option<&'a Texture2D>,
+    pub clouds: Option<&'a Texture2D>,
+    pub opacity: Option<&'a Texture2D>,
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> Texture2D {
+        // 4x4 ajedrez blanco/negro
+        let texels = (0..16)
+            .map(|i| if (i % 4 + i / 4) % 2 == 0 { Vec4::new(1.0, 1.0, 1.0, 1.0) } else { Vec4::new(0.0, 0.0, 0.0, 1.0) })
+            .collect();
+        Texture2D::from_texels(4, 4, texels)
+    }
+
+    #[test]
+    fn downsample_keeps_odd_rows_and_columns() {
+        // 3x3 -> 1x1: el promedio de los nueve
+        let px: Vec<Vec4> = (0..9).map(|i| Vec4::new(i as f32, 0.0, 0.0, 1.0)).collect();
+        let (w, h, out) = downsample_2x2(3, 3, &px);
+        assert_eq!((w, h), (1, 1));
+        assert!((out[0].x - 4.0).abs() < 1e-6);
+        // 5x2 -> 2x1: la columna 4 entra en el último pixel
+        let px: Vec<Vec4> = (0..10).map(|i| Vec4::new((i % 5) as f32, 0.0, 0.0, 1.0)).collect();
+        let (w, h, out) = downsample_2x2(5, 2, &px);
+        assert_eq!((w, h), (2, 1));
+        assert!((out[0].x - 0.5).abs() < 1e-6 && (out[1].x - 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mips_filters_and_wrap() {
+        let t = checker();
+        assert_eq!(t.mip_levels(), 3);
+
+        let near = Sampler::new(Filter::Nearest, Wrap::Repeat);
+        let bil = Sampler::new(Filter::Bilinear, Wrap::Repeat);
+        let tri = Sampler::new(Filter::Trilinear, Wrap::Clamp);
+        // Centro del texel (0,0) de la imagen = esquina superior izquierda
+        let uv = Vec2::new(0.125, 0.875);
+        assert_eq!(t.sample(&near, uv).x, 1.0);
+        // Repeat: desplazar una vuelta completa da lo mismo
+        assert_eq!(t.sample(&near, Vec2::new(1.125, -0.125)).x, 1.0);
+        // Entre cuatro texels el bilineal promedia
+        assert!((t.sample(&bil, Vec2::new(0.25, 0.75)).x - 0.5).abs() < 1e-6);
+        // Los mips de un ajedrez son gris medio; el trilineal interpola hacia ellos
+        assert!((t.sample_lod(&tri, uv, 2.0).x - 0.5).abs() < 1e-6);
+        assert!((t.sample_lod(&tri, uv, 0.5).x - 0.75).abs() < 1e-6);
+        // Un paso de un texel por pixel es lod 0; cuatro texels, lod 2
+        assert!(t.lod(Vec2::new(0.25, 0.0), Vec2::new(0.0, 0.25)).abs() < 1e-6);
+        assert!((t.lod(Vec2::new(1.0, 0.0), Vec2::ZERO) - 2.0).abs() < 1e-6);
+        assert_eq!(Wrap::Mirror.apply(-1, 4), 0);
+        assert_eq!(Wrap::Mirror.apply(4, 4), 3);
+    }
+}
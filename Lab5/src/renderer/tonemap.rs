@@ -0,0 +1,108 @@
+use crate::math::Vec3;
+
+/// Operador para comprimir radiancia HDR al rango [0,1]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Tonemap {
+    /// Sólo exposición y recorte (conserva el look original de los shaders)
+    #[default]
+    Exposure,
+    Reinhard,
+    /// Aproximación filmica ACES (Narkowicz)
+    Aces,
+}
+
+impl Tonemap {
+    pub const ALL: [Tonemap; 3] = [Tonemap::Exposure, Tonemap::Reinhard, Tonemap::Aces];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tonemap::Exposure => "exposure",
+            Tonemap::Reinhard => "reinhard",
+            Tonemap::Aces     => "aces",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        Self::ALL.iter().copied().find(|t| t.name() == s)
+    }
+
+    /// Siguiente operador (para alternar en runtime)
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&t| t == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        let x = x.max(0.0);
+        let y = match self {
+            Tonemap::Exposure => x,
+            Tonemap::Reinhard => x / (1.0 + x),
+            Tonemap::Aces => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                (x*(a*x + b)) / (x*(c*x + d) + e)
+            }
+        };
+        y.clamp(0.0, 1.0)
+    }
+}
+
+/// Parámetros del resolve HDR -> LDR
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ToneMapping {
+    pub operator: Tonemap,
+    pub exposure: f32, // en stops (EV): la radiancia se multiplica por 2^exposure
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self { Self { operator: Tonemap::Exposure, exposure: 0.0 } }
+}
+
+impl ToneMapping {
+    /// Radiancia lineal -> color sRGB en [0,1]
+    #[inline]
+    pub fn map(&self, c: Vec3) -> Vec3 {
+        let k = self.exposure.exp2();
+        Vec3::new(
+            linear_to_srgb(self.operator.apply(c.x * k)),
+            linear_to_srgb(self.operator.apply(c.y * k)),
+            linear_to_srgb(self.operator.apply(c.z * k)),
+        )
+    }
+}
+
+/// Decodifica un canal sRGB a lineal (acepta valores > 1)
+#[inline]
+pub fn srgb_to_linear(x: f32) -> f32 {
+    let x = x.max(0.0);
+    if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Codifica un canal lineal en [0,1] a sRGB
+#[inline]
+pub fn linear_to_srgb(x: f32) -> f32 {
+    let x = x.clamp(0.0, 1.0);
+    if x <= 0.0031308 { x * 12.92 } else { 1.055 * x.powf(1.0 / 2.4) - 0.055 }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_roundtrip_and_operators() {
+        for i in 0..=255 {
+            let x = i as f32 / 255.0;
+            assert!((linear_to_srgb(srgb_to_linear(x)) - x).abs() < 1e-4);
+        }
+        // Exposure 0 con el operador por defecto no altera valores en [0,1]
+        let tm = ToneMapping::default();
+        assert!((tm.map(Vec3::new(0.5, 0.5, 0.5)).x - linear_to_srgb(0.5)).abs() < 1e-6);
+        // Reinhard y ACES comprimen radiancia alta por debajo de 1
+        for op in [Tonemap::Reinhard, Tonemap::Aces] {
+            assert!(op.apply(4.0) < 1.0 && op.apply(4.0) > op.apply(2.0));
+        }
+    }
+}
@@ -1,5 +1,9 @@
 use crate::math::{Vec3, Mat4};
 use crate::shaders::noise::NoiseType;
+use crate::shaders::blackbody::SUN_KELVIN;
+use super::texture::TextureSet;
+use super::shadow::ShadowMap;
+use super::light::{Light, Lights};
 
 #[derive(Copy, Clone, Debug)]
 pub struct PlanetParams {
@@ -10,6 +14,10 @@ pub struct PlanetParams {
     pub rotation_speed: f32,
     pub has_rings: bool,
     pub has_moon: bool,
+    pub has_atmosphere: bool,
+    pub atmosphere: AtmosphereParams,
+    pub has_clouds: bool,
+    pub clouds: CloudParams,
 }
 
 impl Default for PlanetParams {
@@ -22,57 +30,136 @@ impl Default for PlanetParams {
             rotation_speed: 0.5,
             has_rings: false,
             has_moon: false,
+            has_atmosphere: false,
+            atmosphere: AtmosphereParams::default(),
+            has_clouds: false,
+            clouds: CloudParams::default(),
         }
     }
 }
 
+/// Atmósfera de un planeta (dispersión Rayleigh + Mie). Las distancias están en km
+/// y `planet_radius` fija la escala: el cuerpo mide eso, sea cual sea su tamaño en la escena.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AtmosphereParams {
+    pub planet_radius: f32,   // radio del planeta (km)
+    pub height: f32,          // espesor de la capa sobre la superficie (km)
+    pub rayleigh_height: f32, // altura de escala de las moléculas (km)
+    pub mie_height: f32,      // altura de escala de los aerosoles (km)
+    pub rayleigh: Vec3,       // coeficientes de dispersión Rayleigh por canal (1/km)
+    pub mie: f32,             // coeficiente de dispersión Mie (1/km)
+    pub mie_g: f32,           // asimetría de Henyey-Greenstein (0 = isótropo, → 1 hacia adelante)
+}
+
+impl Default for AtmosphereParams {
+    /// Parecida a la Tierra, pero sobre un planeta de 1000 km para que la capa se vea
+    fn default() -> Self {
+        Self {
+            planet_radius: 1000.0,
+            height: 100.0,
+            rayleigh_height: 12.0,
+            mie_height: 2.4,
+            rayleigh: Vec3::new(5.8e-3, 13.5e-3, 33.1e-3),
+            mie: 4e-3,
+            mie_g: 0.76,
+        }
+    }
+}
+
+/// Capa de nubes de un planeta
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CloudParams {
+    pub coverage: f32,   // 0..1 fracción del cielo cubierta
+    pub density: f32,    // 0..1 opacidad de una nube densa
+    pub wind_speed: f32, // giro propio de la capa (rad/s), aparte del de la superficie
+    pub altitude: f32,   // altura de la capa, en radios del planeta
+    pub scale: f32,      // tamaño de los sistemas de nubes (frecuencia del ruido)
+}
+
+impl Default for CloudParams {
+    fn default() -> Self {
+        Self { coverage: 0.5, density: 0.9, wind_speed: 0.12, altitude: 0.06, scale: 1.2 }
+    }
+}
+
 /// Parámetros específicos de la estrella (ajustables en tiempo real)
 #[derive(Copy, Clone, Debug)]
 pub struct StarParams {
-    pub temp_norm: f32,          // 0..1 temperatura relativa
+    pub temperature: f32,        // temperatura efectiva en Kelvin
     pub flare_intensity: f32,    // escala de llamaradas
     pub noise_scale: f32,        // escala espacial de ruido
     pub rot_speed: f32,          // velocidad de rotación
     pub noise_type: NoiseType,   // tipo de ruido a usar
     pub use_cellular_flares: bool, // si usar cellular específicamente para flares
+    pub limb_darkening: f32,     // coeficiente lineal: I(μ) = 1 - k(1 - μ), μ = n·v
+    pub granulation: f32,        // contraste de las celdas de granulación (0 = sin celdas)
+    pub granule_scale: f32,      // celdas por radio de la estrella
+    pub spot_coverage: f32,      // 0..1 fracción de grupos de manchas activos
+    pub spot_latitude: f32,      // latitud máxima de las manchas (grados)
 }
 
 impl Default for StarParams {
     fn default() -> Self {
         Self {
-            temp_norm: 0.65,
+            temperature: SUN_KELVIN,
             flare_intensity: 0.45,
             noise_scale: 1.5,
             rot_speed: 0.15,
             noise_type: NoiseType::Perlin,
             use_cellular_flares: false,
+            limb_darkening: 0.6,
+            granulation: 0.3,
+            granule_scale: 14.0,
+            spot_coverage: 0.5,
+            spot_latitude: 30.0,
         }
     }
 }
 
+/// Uniforms de un draw; `'a` es la vida de las texturas enlazadas
 #[derive(Copy, Clone, Debug)]
-pub struct Uniforms {
+pub struct Uniforms<'a> {
     pub time: f32,
-    pub light_dir: Vec3,
+    pub lights: Lights, // la primera es la principal (la del mapa de sombras)
     pub view: Mat4,
     pub proj: Mat4,
     pub model: Mat4,
+    pub mesh_radius: f32, // radio medio de la malla del nodo (`Mesh::mean_radius`)
     pub camera_pos: Vec3,
     pub planet: PlanetParams,
     pub star: StarParams,
+    pub textures: TextureSet<'a>,
+    pub shadow: Option<&'a ShadowMap>, // mapa de sombras de la luz principal (None: sin sombras)
 }
 
-impl Default for Uniforms {
+impl Default for Uniforms<'_> {
     fn default() -> Self {
         Self {
             time: 0.0,
-            light_dir: Vec3::new(0.5, 0.7, 0.2).normalize(),
+            lights: Lights::single(Light::default()),
             view: Mat4::identity(),
             proj: Mat4::identity(),
             model: Mat4::identity(),
+            mesh_radius: 0.5, // assets/sphere.obj
             camera_pos: Vec3::new(0.0, 0.0, 3.0),
             planet: PlanetParams::default(),
             star: StarParams::default(),
+            textures: TextureSet::default(),
+            shadow: None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Uniforms para los tests del renderer: cámara en (0, 0, 3.5) mirando al origen con
+/// 60° de fov (la cámara inicial del viewer) y relación de aspecto `aspect`
+#[cfg(test)]
+pub(crate) fn test_uniforms(aspect: f32) -> Uniforms<'static> {
+    use crate::math::mat::{look_at_rh, perspective_rh};
+    let eye = Vec3::new(0.0, 0.0, 3.5);
+    Uniforms {
+        view: look_at_rh(eye, Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0)),
+        proj: perspective_rh(60.0_f32.to_radians(), aspect, 0.01, 100.0),
+        camera_pos: eye,
+        ..Uniforms::default()
+    }
+}
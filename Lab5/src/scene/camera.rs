@@ -37,10 +37,18 @@ impl Camera {
     pub fn new_pivot(eye: Vec3, target: Vec3, aspect: f32) -> Self {
         let mut c = Self { aspect, ..Default::default() };
         c.eye = eye;
-        c.center = target;
+        c.aim_at(target);
         c
     }
 
+    /// Mira hacia `target` dejando yaw/pitch coherentes con el modo free-fly
+    pub fn aim_at(&mut self, target: Vec3) {
+        let d = (target - self.eye).normalize();
+        self.center = target;
+        self.pitch = d.y.clamp(-1.0, 1.0).asin();
+        self.yaw = (-d.x).atan2(-d.z);
+    }
+
     pub fn view(&self) -> Mat4 { look_at_rh(self.eye, self.center, self.up) }
     pub fn proj(&self) -> Mat4 { perspective_rh(self.fov_y, self.aspect, self.znear, self.zfar) }
 
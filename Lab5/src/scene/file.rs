@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::math::Vec3;
+use crate::renderer::{AtmosphereParams, CloudParams, PlanetParams, Texture2D, buffers::Color};
+use crate::renderer::uniforms::StarParams;
+use crate::shaders::{ShaderKind, noise::NoiseType};
+use super::{load_obj, Camera, Node, Orbit, Scene, Transform};
+
+/// Escena declarativa en TOML: cámara, luz, fondo y cuerpos (malla, shader,
+/// transform, parámetros y órbita). Ejemplo en `scenes/sistema.toml`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct SceneFile {
+    pub background: Option<[u8; 3]>,
+    pub camera: CameraDesc,
+    pub light: LightDesc,
+    #[serde(rename = "body")]
+    pub bodies: Vec<BodyDesc>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields, default)]
+pub struct CameraDesc {
+    pub eye: [f32; 3],
+    pub target: [f32; 3],
+    pub fov: f32, // grados
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+impl Default for CameraDesc {
+    fn default() -> Self {
+        let c = Camera::default();
+        Self {
+            eye: [c.eye.x, c.eye.y, c.eye.z],
+            target: [0.0, 0.0, 0.0],
+            fov: c.fov_y.to_degrees(),
+            znear: c.znear,
+            zfar: c.zfar,
+        }
+    }
+}
+
+/// Luz direccional (solo se usa si no hay estrella en la escena)
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct LightDesc {
+    pub direction: Option<[f32; 3]>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BodyDesc {
+    pub name: String,
+    pub shader: String,
+    pub mesh: Option<String>,
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3], // grados
+    #[serde(default = "one")]
+    pub scale: f32,
+    pub orbit: Option<OrbitDesc>,
+    #[serde(default)]
+    pub planet: PlanetDesc,
+    #[serde(default)]
+    pub star: StarDesc,
+    pub albedo_map: Option<String>, // color (sRGB)
+    pub clouds_map: Option<String>, // cobertura de nubes (lineal)
+    pub rings_map: Option<String>,  // opacidad radial de los anillos (lineal)
+}
+
+fn one() -> f32 { 1.0 }
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OrbitDesc {
+    pub radius: f32,
+    #[serde(default)]
+    pub speed: f32,
+    #[serde(default)]
+    pub phase: f32,       // grados
+    #[serde(default)]
+    pub inclination: f32, // grados
+}
+
+/// Campos opcionales de `PlanetParams` (los que falten toman el default)
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct PlanetDesc {
+    pub base_color: Option<[u8; 3]>,
+    pub band_freq: Option<f32>,
+    pub noise_scale: Option<f32>,
+    pub rim_power: Option<f32>,
+    pub rotation_speed: Option<f32>,
+    pub has_rings: Option<bool>,
+    pub has_moon: Option<bool>,
+    pub has_atmosphere: Option<bool>,
+    pub atmosphere: AtmosphereDesc,
+    pub has_clouds: Option<bool>,
+    pub clouds: CloudDesc,
+}
+
+/// Campos opcionales de `CloudParams`
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct CloudDesc {
+    pub coverage: Option<f32>,
+    pub density: Option<f32>,
+    pub wind_speed: Option<f32>, // rad/s
+    pub altitude: Option<f32>,   // radios del planeta
+    pub scale: Option<f32>,
+}
+
+/// Campos opcionales de `AtmosphereParams` (distancias en km)
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct AtmosphereDesc {
+    pub planet_radius: Option<f32>,
+    pub height: Option<f32>,
+    pub rayleigh_height: Option<f32>,
+    pub mie_height: Option<f32>,
+    pub rayleigh: Option<[f32; 3]>, // 1/km por canal
+    pub mie: Option<f32>,           // 1/km
+    pub mie_g: Option<f32>,
+}
+
+/// Campos opcionales de `StarParams`
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct StarDesc {
+    pub temperature: Option<f32>, // Kelvin
+    pub flare_intensity: Option<f32>,
+    pub noise_scale: Option<f32>,
+    pub rot_speed: Option<f32>,
+    pub noise_type: Option<String>,
+    pub cellular_flares: Option<bool>,
+    pub limb_darkening: Option<f32>,
+    pub granulation: Option<f32>,
+    pub granule_scale: Option<f32>,
+    pub spot_coverage: Option<f32>,
+    pub spot_latitude: Option<f32>, // grados
+}
+
+fn vec3(v: [f32; 3]) -> Vec3 { Vec3::new(v[0], v[1], v[2]) }
+
+/// Carga (una sola vez por ruta) una textura y devuelve su índice en la escena
+fn texture(
+    scene: &mut Scene,
+    cache: &mut HashMap<(String, bool), usize>,
+    base_dir: &Path,
+    file: &str,
+    srgb: bool,
+    field: &str,
+) -> Result<usize, String> {
+    let path = base_dir.join(file).to_string_lossy().into_owned();
+    if let Some(&t) = cache.get(&(path.clone(), srgb)) {
+        return Ok(t);
+    }
+    let t = scene.add_texture(Texture2D::load(&path, srgb).map_err(|e| format!("{}: {}", field, e))?);
+    cache.insert((path, srgb), t);
+    Ok(t)
+}
+
+impl PlanetDesc {
+    fn params(&self, field: &str) -> Result<PlanetParams, String> {
+        let d = PlanetParams::default();
+        Ok(PlanetParams {
+            base_color: self.base_color.map_or(d.base_color, |c| Color::rgb(c[0], c[1], c[2])),
+            band_freq: self.band_freq.unwrap_or(d.band_freq),
+            noise_scale: self.noise_scale.unwrap_or(d.noise_scale),
+            rim_power: self.rim_power.unwrap_or(d.rim_power),
+            rotation_speed: self.rotation_speed.unwrap_or(d.rotation_speed),
+            has_rings: self.has_rings.unwrap_or(d.has_rings),
+            has_moon: self.has_moon.unwrap_or(d.has_moon),
+            has_atmosphere: self.has_atmosphere.unwrap_or(d.has_atmosphere),
+            atmosphere: self.atmosphere.params(&format!("{}.atmosphere", field))?,
+            has_clouds: self.has_clouds.unwrap_or(d.has_clouds),
+            clouds: self.clouds.params(&format!("{}.clouds", field))?,
+        })
+    }
+}
+
+impl CloudDesc {
+    fn params(&self, field: &str) -> Result<CloudParams, String> {
+        let d = CloudParams::default();
+        let unit = |v: Option<f32>, def: f32, name: &str| match v {
+            Some(x) if !(0.0..=1.0).contains(&x) => Err(format!("{}.{}: debe estar entre 0 y 1 (vale {})", field, name, x)),
+            v => Ok(v.unwrap_or(def)),
+        };
+        Ok(CloudParams {
+            coverage: unit(self.coverage, d.coverage, "coverage")?,
+            density: unit(self.density, d.density, "density")?,
+            wind_speed: self.wind_speed.unwrap_or(d.wind_speed),
+            altitude: self.altitude.unwrap_or(d.altitude),
+            scale: self.scale.unwrap_or(d.scale),
+        })
+    }
+}
+
+impl AtmosphereDesc {
+    fn params(&self, field: &str) -> Result<AtmosphereParams, String> {
+        let d = AtmosphereParams::default();
+        let positive = |v: Option<f32>, def: f32, name: &str| match v {
+            Some(x) if x <= 0.0 => Err(format!("{}.{}: debe ser mayor que 0 (vale {})", field, name, x)),
+            v => Ok(v.unwrap_or(def)),
+        };
+        let mie_g = self.mie_g.unwrap_or(d.mie_g);
+        if !(-1.0 < mie_g && mie_g < 1.0) {
+            return Err(format!("{}.mie_g: debe estar entre -1 y 1 (vale {})", field, mie_g));
+        }
+        Ok(AtmosphereParams {
+            planet_radius: positive(self.planet_radius, d.planet_radius, "planet_radius")?,
+            height: positive(self.height, d.height, "height")?,
+            rayleigh_height: positive(self.rayleigh_height, d.rayleigh_height, "rayleigh_height")?,
+            mie_height: positive(self.mie_height, d.mie_height, "mie_height")?,
+            rayleigh: self.rayleigh.map_or(d.rayleigh, vec3),
+            mie: self.mie.unwrap_or(d.mie),
+            mie_g,
+        })
+    }
+}
+
+impl StarDesc {
+    fn params(&self, field: &str) -> Result<StarParams, String> {
+        let d = StarParams::default();
+        let noise_type = match &self.noise_type {
+            Some(n) => NoiseType::from_name(n)
+                .ok_or_else(|| format!("{}.noise_type: ruido desconocido '{}' (opciones: perlin, simplex, cellular)", field, n))?,
+            None => d.noise_type,
+        };
+        Ok(StarParams {
+            temperature: self.temperature.unwrap_or(d.temperature),
+            flare_intensity: self.flare_intensity.unwrap_or(d.flare_intensity),
+            noise_scale: self.noise_scale.unwrap_or(d.noise_scale),
+            rot_speed: self.rot_speed.unwrap_or(d.rot_speed),
+            noise_type,
+            use_cellular_flares: self.cellular_flares.unwrap_or(d.use_cellular_flares),
+            limb_darkening: self.limb_darkening.unwrap_or(d.limb_darkening),
+            granulation: self.granulation.unwrap_or(d.granulation),
+            granule_scale: self.granule_scale.unwrap_or(d.granule_scale),
+            spot_coverage: self.spot_coverage.unwrap_or(d.spot_coverage),
+            spot_latitude: self.spot_latitude.unwrap_or(d.spot_latitude),
+        })
+    }
+}
+
+impl SceneFile {
+    /// Parsea el texto TOML; los errores de sintaxis/tipos indican línea, columna y campo
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    /// Lee y parsea un archivo de escena
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("No pude abrir '{}': {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Cámara descrita en el archivo; los valores que no dejan ver nada son error
+    pub fn camera(&self, aspect: f32) -> Result<Camera, String> {
+        let c = &self.camera;
+        if !(c.fov > 0.0 && c.fov < 180.0) {
+            return Err(format!("camera.fov: debe estar entre 0 y 180 grados (es {})", c.fov));
+        }
+        if c.znear.is_nan() || c.znear <= 0.0 {
+            return Err(format!("camera.znear: debe ser mayor que 0 (es {})", c.znear));
+        }
+        if c.zfar.is_nan() || c.zfar <= c.znear {
+            return Err(format!("camera.zfar: debe ser mayor que znear = {} (es {})", c.znear, c.zfar));
+        }
+        if (vec3(c.eye) - vec3(c.target)).length() < 1e-6 {
+            return Err("camera.target: no puede coincidir con camera.eye".to_string());
+        }
+        let mut cam = Camera::new_pivot(vec3(c.eye), vec3(c.target), aspect);
+        cam.fov_y = c.fov.to_radians();
+        cam.znear = c.znear;
+        cam.zfar = c.zfar;
+        Ok(cam)
+    }
+
+    /// Construye la escena. Las rutas de malla son relativas a `base_dir`;
+    /// los cuerpos sin `mesh` usan `default_mesh`.
+    pub fn build(&self, base_dir: &Path, default_mesh: &str) -> Result<Scene, String> {
+        if self.bodies.is_empty() {
+            return Err("la escena no tiene cuerpos ([[body]])".to_string());
+        }
+        let mut scene = Scene::new();
+        if let Some(c) = self.background {
+            scene.background = Color::rgb(c[0], c[1], c[2]);
+        }
+        if let Some(d) = self.light.direction {
+            let d = vec3(d);
+            if d.length() < 1e-6 {
+                return Err("light.direction: no puede ser el vector cero".to_string());
+            }
+            scene.light_dir = d.normalize();
+        }
+
+        let mut meshes: HashMap<String, usize> = HashMap::new();
+        let mut textures = HashMap::new();
+        for (i, b) in self.bodies.iter().enumerate() {
+            let field = format!("body[{}] ('{}')", i, b.name);
+
+            let kind = ShaderKind::from_name(&b.shader).ok_or_else(|| {
+                let names: Vec<_> = ShaderKind::ALL.iter().map(|k| k.name()).collect();
+                format!("{}.shader: shader desconocido '{}' (opciones: {})", field, b.shader, names.join(", "))
+            })?;
+            if scene.find(&b.name).is_some() {
+                return Err(format!("{}.name: nombre repetido", field));
+            }
+            if b.scale <= 0.0 || !b.scale.is_finite() {
+                return Err(format!("{}.scale: debe ser mayor que 0", field));
+            }
+
+            let path = match &b.mesh {
+                Some(m) => base_dir.join(m).to_string_lossy().into_owned(),
+                None => default_mesh.to_string(),
+            };
+            let mesh = match meshes.get(&path) {
+                Some(&m) => m,
+                None => {
+                    let m = scene.add_mesh(load_obj(&path).map_err(|e| format!("{}.mesh: {}", field, e))?);
+                    meshes.insert(path, m);
+                    m
+                }
+            };
+
+            let mut node = Node::new(&b.name, kind, mesh);
+            node.parent = match &b.parent {
+                Some(p) => Some(scene.find(p).ok_or_else(|| {
+                    format!("{}.parent: no hay un cuerpo '{}' definido antes de este", field, p)
+                })?),
+                None => None,
+            };
+            node.transform = Transform {
+                translation: vec3(b.position),
+                rotation: vec3(b.rotation.map(f32::to_radians)),
+                scale: Vec3::from_scalar(b.scale),
+            };
+            node.orbit = b.orbit.as_ref().map(|o| Orbit {
+                radius: o.radius,
+                speed: o.speed,
+                phase: o.phase.to_radians(),
+                inclination: o.inclination.to_radians(),
+            });
+            node.star = b.star.params(&format!("{}.star", field))?;
+            if let Some(m) = &b.albedo_map {
+                let f = format!("{}.albedo_map", field);
+                node.textures.albedo = Some(texture(&mut scene, &mut textures, base_dir, m, true, &f)?);
+            }
+            if let Some(m) = &b.clouds_map {
+                let f = format!("{}.clouds_map", field);
+                node.textures.clouds = Some(texture(&mut scene, &mut textures, base_dir, m, false, &f)?);
+            }
+            let id = scene.add_planet(node, b.planet.params(&format!("{}.planet", field))?);
+
+            // El mapa de anillos va al nodo `Rings` (el hijo creado por has_rings o el propio cuerpo)
+            if let Some(m) = &b.rings_map {
+                let f = format!("{}.rings_map", field);
+                let target = scene.child_of_kind(id, ShaderKind::Rings)
+                    .or((kind == ShaderKind::Rings).then_some(id))
+                    .ok_or_else(|| format!("{}: el cuerpo no tiene anillos (usa planet.has_rings)", f))?;
+                scene.nodes[target].textures.opacity = Some(texture(&mut scene, &mut textures, base_dir, m, false, &f)?);
+            }
+        }
+        Ok(scene)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_file_builds_and_reports_fields() {
+        let text = r#"
+            background = [0, 0, 10]
+            [camera]
+            eye = [0, 2, 8]
+
+            [[body]]
+            name = "sol"
+            shader = "star"
+            scale = 2.0
+            star = { temperature = 9000.0, noise_type = "cellular" }
+
+            [[body]]
+            name = "gas"
+            shader = "gas"
+            parent = "sol"
+            orbit = { radius = 5.0, speed = 0.3 }
+            planet = { has_rings = true, has_atmosphere = true, atmosphere = { height = 60.0 } }
+        "#;
+        let f = SceneFile::parse(text).unwrap();
+        let scene = f.build(Path::new("."), "assets/sphere.obj").unwrap();
+        assert_eq!(scene.nodes.len(), 5); // + corona, anillos y atmósfera
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.nodes[0].star.noise_type, NoiseType::Cellular);
+        assert_eq!(scene.nodes[0].star.temperature, 9000.0);
+        let gas = scene.find("gas").unwrap();
+        assert_eq!(scene.nodes[gas].parent, Some(0));
+        assert_eq!(scene.nodes[gas].planet.atmosphere.height, 60.0);
+
+        let err = SceneFile::parse(&text.replace("speed = 0.3", "sped = 0.3")).unwrap_err();
+        assert!(err.contains("sped"), "{}", err);
+        let err = SceneFile::parse(&text.replace("shader = \"gas\"", "shader = \"nope\"")).unwrap()
+            .build(Path::new("."), "assets/sphere.obj").unwrap_err();
+        assert!(err.contains("body[1]") && err.contains("shader"), "{}", err);
+        let err = SceneFile::parse(&text.replace("parent = \"sol\"", "parent = \"luna\"")).unwrap()
+            .build(Path::new("."), "assets/sphere.obj").unwrap_err();
+        assert!(err.contains("body[1]") && err.contains("parent"), "{}", err);
+        let err = SceneFile::parse(&text.replace("height = 60.0", "height = -1.0")).unwrap()
+            .build(Path::new("."), "assets/sphere.obj").unwrap_err();
+        assert!(err.contains("body[1]") && err.contains("planet.atmosphere.height"), "{}", err);
+    }
+
+    #[test]
+    fn camera_rejects_degenerate_views() {
+        let camera = |fields: &str| SceneFile::parse(&format!("[camera]\n{}\n", fields)).unwrap().camera(1.5);
+        assert!(camera("eye = [0, 2, 8]\nfov = 45.0").is_ok());
+        for (fields, culprit) in [
+            ("fov = 0.0", "camera.fov"),
+            ("fov = 180.0", "camera.fov"),
+            ("znear = -1.0", "camera.znear"),
+            ("znear = 1.0\nzfar = 1.0", "camera.zfar"),
+            ("znear = 0.1\nzfar = -5.0", "camera.zfar"),
+            ("eye = [1, 2, 3]\ntarget = [1, 2, 3]", "camera.target"),
+        ] {
+            let err = camera(fields).unwrap_err();
+            assert!(err.starts_with(culprit), "{}: {}", fields, err);
+        }
+    }
+}
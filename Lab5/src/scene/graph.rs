@@ -0,0 +1,565 @@
+use crate::math::{Mat4, Vec3, Vec4};
+use crate::math::mat::{rotation_x, rotation_y, rotation_z, scale, translate};
+use crate::renderer::{Light, LightKind, Lights, PlanetParams, RenderQueue, RenderState, ShadowMap, Uniforms, Texture2D, TextureSet, buffers::Color};
+use crate::renderer::pipeline::draw_mesh;
+use crate::renderer::uniforms::StarParams;
+use crate::shaders::{atmosphere::Atmosphere, corona, ShaderKind, ShaderLibrary};
+use super::Mesh;
+
+/// Posición de un `model` (su columna de traslación)
+fn origin(m: &Mat4) -> Vec3 { Vec3::new(m.m[0][3], m.m[1][3], m.m[2][3]) }
+
+/// Índice de un nodo dentro de [`Scene::nodes`]
+pub type NodeId = usize;
+
+/// Transformación local (rotación en euler: Y * X * Z)
+#[derive(Copy, Clone, Debug)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Vec3, // radianes
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self { translation: Vec3::ZERO, rotation: Vec3::ZERO, scale: Vec3::from_scalar(1.0) }
+    }
+}
+
+impl Transform {
+    pub fn uniform(s: f32) -> Self {
+        Self { scale: Vec3::from_scalar(s), ..Self::default() }
+    }
+
+    /// Rotación * escala (la traslación se aplica aparte en el frame del nodo)
+    pub fn rotation_scale(&self) -> Mat4 {
+        rotation_y(self.rotation.y) * rotation_x(self.rotation.x) * rotation_z(self.rotation.z) * scale(self.scale)
+    }
+}
+
+/// Órbita circular alrededor del origen del padre
+#[derive(Copy, Clone, Debug)]
+pub struct Orbit {
+    pub radius: f32,
+    pub speed: f32,       // rad/s
+    pub phase: f32,       // ángulo inicial (rad)
+    pub inclination: f32, // inclinación del plano orbital (rad)
+}
+
+impl Orbit {
+    pub fn new(radius: f32, speed: f32) -> Self {
+        Self { radius, speed, phase: 0.0, inclination: 0.0 }
+    }
+
+    /// Posición relativa al padre en el tiempo `t`
+    pub fn offset(&self, t: f32) -> Vec3 {
+        let a = self.phase + self.speed * t;
+        let p = Vec4::new(self.radius * a.cos(), 0.0, -self.radius * a.sin(), 1.0);
+        (rotation_x(self.inclination) * p).xyz()
+    }
+}
+
+/// Texturas de un nodo (índices en `Scene::textures`)
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TextureSlots {
+    pub albedo: Option<usize>,
+    pub clouds: Option<usize>,
+    pub opacity: Option<usize>,
+}
+
+/// Un cuerpo de la escena
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub transform: Transform,
+    pub orbit: Option<Orbit>,
+    pub mesh: usize, // índice en `Scene::meshes`
+    pub shader: ShaderKind,
+    pub planet: PlanetParams,
+    pub star: StarParams,
+    pub state: RenderState,
+    pub textures: TextureSlots,
+    pub visible: bool,
+}
+
+impl Node {
+    pub fn new(name: &str, shader: ShaderKind, mesh: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            parent: None,
+            transform: Transform::default(),
+            orbit: None,
+            mesh,
+            shader,
+            planet: PlanetParams::default(),
+            star: StarParams::default(),
+            state: shader.render_state(),
+            textures: TextureSlots::default(),
+            visible: true,
+        }
+    }
+}
+
+/// Fondo negro espacial (default de `Scene::background`)
+pub const CLEAR_COLOR: Color = Color::rgb(2, 2, 5);
+
+/// Grafo de escena: los hijos se mueven con el frame del padre (posición y órbita),
+/// pero no heredan su escala ni su giro propio.
+#[derive(Clone, Debug)]
+pub struct Scene {
+    pub meshes: Vec<Mesh>,
+    pub textures: Vec<Texture2D>,
+    pub nodes: Vec<Node>,
+    pub background: Color,
+    pub light_dir: Vec3, // luz direccional cuando la escena no tiene estrella
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            meshes: Vec::new(),
+            textures: Vec::new(),
+            nodes: Vec::new(),
+            background: CLEAR_COLOR,
+            light_dir: Vec3::new(0.5, 0.7, 0.2).normalize(),
+        }
+    }
+}
+
+impl Scene {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add_mesh(&mut self, mesh: Mesh) -> usize {
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    pub fn add_texture(&mut self, tex: Texture2D) -> usize {
+        self.textures.push(tex);
+        self.textures.len() - 1
+    }
+
+    /// Agrega un nodo; el padre (si hay) debe haberse agregado antes
+    pub fn add_node(&mut self, node: Node) -> NodeId {
+        if let Some(p) = node.parent {
+            assert!(p < self.nodes.len(), "el padre de '{}' no existe", node.name);
+        }
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Agrega un planeta y, según `params.has_rings` / `params.has_moon` /
+    /// `params.has_clouds` / `params.has_atmosphere`, sus anillos, su luna, sus nubes y
+    /// su atmósfera como hijos.
+    /// Si es una estrella, además su corona.
+    pub fn add_planet(&mut self, mut node: Node, params: PlanetParams) -> NodeId {
+        node.planet = params;
+        let star = node.shader == ShaderKind::Star;
+        let id = self.add_node(node);
+        if params.has_rings { self.attach(id, ShaderKind::Rings); }
+        if params.has_moon { self.attach(id, ShaderKind::Moon); }
+        if params.has_clouds { self.attach(id, ShaderKind::Clouds); }
+        if params.has_atmosphere { self.attach(id, ShaderKind::Atmosphere); }
+        if star { self.attach(id, ShaderKind::Corona); }
+        id
+    }
+
+    /// Crea el hijo `Rings`, `Moon`, `Clouds`, `Atmosphere` o `Corona` de `parent`, dimensionado
+    /// según su escala
+    fn attach(&mut self, parent: NodeId, kind: ShaderKind) -> NodeId {
+        let p = &self.nodes[parent];
+        let s = p.transform.scale.x;
+        let suffix = match kind {
+            ShaderKind::Rings => "anillos",
+            ShaderKind::Corona => "corona",
+            ShaderKind::Atmosphere => "atmosfera",
+            ShaderKind::Clouds => "nubes",
+            _ => "luna",
+        };
+        let mut child = Node::new(&format!("{}-{}", p.name, suffix), kind, p.mesh);
+        child.parent = Some(parent);
+        child.planet = p.planet;
+        child.star = p.star;
+        match kind {
+            ShaderKind::Rings => child.transform = Transform::uniform(s),
+            ShaderKind::Corona => child.transform = Transform::uniform(s * corona::SHELL),
+            ShaderKind::Atmosphere => child.transform = Transform::uniform(s * Atmosphere::shell_scale(&p.planet.atmosphere)),
+            ShaderKind::Clouds => child.transform = Transform::uniform(s * (1.0 + p.planet.clouds.altitude)),
+            _ => {
+                child.transform = Transform::uniform(s * 0.3);
+                child.orbit = Some(Orbit { radius: s * 2.0, speed: 0.4, phase: 0.0, inclination: 0.3 });
+            }
+        }
+        self.add_node(child)
+    }
+
+    /// Hijo directo de `parent` con ese shader
+    pub fn child_of_kind(&self, parent: NodeId, kind: ShaderKind) -> Option<NodeId> {
+        self.nodes.iter().position(|n| n.parent == Some(parent) && n.shader == kind)
+    }
+
+    /// Muestra u oculta los anillos (`Rings`), la luna (`Moon`), las nubes (`Clouds`) o la
+    /// atmósfera (`Atmosphere`) de un cuerpo,
+    /// creándolos la primera vez. Devuelve si quedaron visibles.
+    pub fn toggle_child(&mut self, parent: NodeId, kind: ShaderKind) -> bool {
+        let on = match self.child_of_kind(parent, kind) {
+            Some(c) => {
+                self.nodes[c].visible = !self.nodes[c].visible;
+                self.nodes[c].visible
+            }
+            None => {
+                self.attach(parent, kind);
+                true
+            }
+        };
+        let planet = &mut self.nodes[parent].planet;
+        match kind {
+            ShaderKind::Rings => planet.has_rings = on,
+            ShaderKind::Atmosphere => planet.has_atmosphere = on,
+            ShaderKind::Clouds => planet.has_clouds = on,
+            _ => planet.has_moon = on,
+        }
+        on
+    }
+
+    /// Cambia el shader de un nodo (y su estado de render recomendado). La corona
+    /// acompaña al shader `Star`: se crea o se muestra con él y se oculta sin él.
+    pub fn set_shader(&mut self, id: NodeId, kind: ShaderKind) {
+        let n = &mut self.nodes[id];
+        n.shader = kind;
+        n.state = kind.render_state();
+        let star = kind == ShaderKind::Star;
+        match self.child_of_kind(id, ShaderKind::Corona) {
+            Some(c) => self.nodes[c].visible = star,
+            None if star => { self.attach(id, ShaderKind::Corona); }
+            None => {}
+        }
+    }
+
+    /// Cuerpos seleccionables (todo salvo anillos, lunas y las capas: nubes, atmósferas y coronas)
+    pub fn bodies(&self) -> Vec<NodeId> {
+        (0..self.nodes.len())
+            .filter(|&i| !matches!(self.nodes[i].shader, ShaderKind::Rings | ShaderKind::Moon | ShaderKind::Clouds | ShaderKind::Atmosphere | ShaderKind::Corona))
+            .collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    /// Primer nodo con ese shader
+    pub fn find_kind(&self, kind: ShaderKind) -> Option<NodeId> {
+        self.nodes.iter().position(|n| n.shader == kind)
+    }
+
+    /// Matrices `model` de todos los nodos en el tiempo `t`
+    pub fn world_transforms(&self, t: f32) -> Vec<Mat4> {
+        let mut frames: Vec<Mat4> = Vec::with_capacity(self.nodes.len());
+        let mut models = Vec::with_capacity(self.nodes.len());
+        for n in &self.nodes {
+            let parent = n.parent.map(|p| frames[p]).unwrap_or_else(Mat4::identity);
+            let mut offset = n.transform.translation;
+            if let Some(o) = &n.orbit {
+                offset += o.offset(t);
+            }
+            let frame = parent * translate(offset);
+            models.push(frame * n.transform.rotation_scale());
+            frames.push(frame);
+        }
+        models
+    }
+
+    /// Luces del frame: una puntual en el centro de cada nodo visible cuyo shader emite
+    /// luz (`Shader::emitted_light`, p.ej. las estrellas, con su color y luminancia),
+    /// junto al nodo que la emite; si no hay ninguna, la direccional `light_dir`.
+    /// La primera es la principal.
+    fn frame_lights(&self, shaders: &ShaderLibrary, models: &[Mat4], base: &Uniforms) -> Vec<(Option<NodeId>, Light)> {
+        let mut lights: Vec<(Option<NodeId>, Light)> = self.nodes.iter().enumerate()
+            .filter(|(_, n)| n.visible)
+            .filter_map(|(i, n)| {
+                let u = Uniforms { model: models[i], planet: n.planet, star: n.star, ..*base };
+                let (color, intensity) = shaders.get(n.shader).emitted_light(&u)?;
+                Some((Some(i), Light::point(origin(&models[i]), color, intensity, 0.0)))
+            })
+            .collect();
+        if lights.is_empty() {
+            lights.push((None, self.fallback_light()));
+        }
+        lights
+    }
+
+    fn fallback_light(&self) -> Light {
+        Light::directional(self.light_dir, Vec3::from_scalar(1.0), 1.0)
+    }
+
+    /// Encola un draw por nodo visible, iluminado por todas las luces del frame
+    /// (ver `frame_lights`).
+    pub fn enqueue<'a>(&'a self, shaders: &'a ShaderLibrary, base: &Uniforms<'a>, queue: &mut RenderQueue<'a>) {
+        let models = self.world_transforms(base.time);
+        let lights = self.frame_lights(shaders, &models, base);
+
+        for (i, (n, model)) in self.nodes.iter().zip(&models).enumerate() {
+            if !n.visible { continue; }
+            let u = self.node_uniforms(i, model, &lights, base);
+            queue.push(&self.meshes[n.mesh], shaders.get(n.shader), u, n.state);
+        }
+    }
+
+    /// Uniforms de un nodo: su `model`, sus parámetros y texturas, y las luces.
+    /// Una estrella no se ilumina con su propia luz; si no queda ninguna usa `light_dir`.
+    /// Las capas (nubes, atmósfera, corona) usan los parámetros actuales de su cuerpo.
+    fn node_uniforms<'a>(&'a self, id: NodeId, model: &Mat4, lights: &[(Option<NodeId>, Light)], base: &Uniforms<'a>) -> Uniforms<'a> {
+        let n = &self.nodes[id];
+        let mut u = *base;
+        u.model = *model;
+        u.mesh_radius = self.meshes[n.mesh].mean_radius();
+        let params = match (n.shader, n.parent) {
+            (ShaderKind::Clouds | ShaderKind::Atmosphere | ShaderKind::Corona, Some(p)) => &self.nodes[p],
+            _ => n,
+        };
+        u.planet = params.planet;
+        u.star = params.star;
+        let tex = |slot: Option<usize>| slot.map(|i| &self.textures[i]);
+        u.textures = TextureSet {
+            albedo: tex(n.textures.albedo),
+            clouds: tex(n.textures.clouds),
+            opacity: tex(n.textures.opacity),
+        };
+        u.lights = Lights::default();
+        for (_, l) in lights.iter().filter(|(src, _)| *src != Some(id)) {
+            u.lights.push(*l);
+        }
+        if u.lights.is_empty() {
+            u.lights.push(self.fallback_light());
+        }
+        u
+    }
+
+    /// Llena `map` con la profundidad de la escena vista desde la luz principal: cubo
+    /// desde una luz puntual o vista ortográfica a lo largo de una direccional.
+    /// Proyectan sombra los nodos visibles opacos (no la estrella ni las capas
+    /// transparentes); cada uno se dibuja con su propio vertex shader.
+    pub fn render_shadows(&self, shaders: &ShaderLibrary, base: &Uniforms, map: &mut ShadowMap) {
+        if !map.is_enabled() { return; }
+        let models = self.world_transforms(base.time);
+        let lights = self.frame_lights(shaders, &models, base);
+        let casters: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| {
+                let n = &self.nodes[i];
+                n.visible && n.shader != ShaderKind::Star && !n.state.is_transparent()
+            })
+            .collect();
+        // Radio de cada nodo en el mundo
+        let radius = |i: usize| {
+            let m = &models[i];
+            let col = |c: usize| Vec3::new(m.m[0][c], m.m[1][c], m.m[2][c]).length();
+            col(0).max(col(1)).max(col(2))
+        };
+
+        match lights[0].1.kind {
+            LightKind::Point { pos, .. } => {
+                let far = (0..self.nodes.len())
+                    .map(|i| (origin(&models[i]) - pos).length() + radius(i))
+                    .fold(1.0, f32::max);
+                map.set_point(pos, 0.1, far * 1.05);
+            }
+            LightKind::Directional { dir } => {
+                let all: Vec<usize> = (0..self.nodes.len()).filter(|&i| self.nodes[i].visible).collect();
+                if all.is_empty() { return; }
+                let center = all.iter().fold(Vec3::ZERO, |acc, &i| acc + origin(&models[i])) * (1.0 / all.len() as f32);
+                let r = all.iter().map(|&i| (origin(&models[i]) - center).length() + radius(i)).fold(1e-3, f32::max);
+                map.set_directional(dir, center, r);
+            }
+        }
+
+        map.render(|fb, view, proj, vp| {
+            for &i in &casters {
+                let n = &self.nodes[i];
+                let u = Uniforms { view, proj, ..self.node_uniforms(i, &models[i], &lights, base) };
+                let state = RenderState { threads: n.state.threads, ..RenderState::shadow_caster() };
+                draw_mesh(fb, &self.meshes[n.mesh], shaders.get(n.shader), &u, vp, &state);
+            }
+        });
+    }
+
+    /// Un solo cuerpo centrado (el modo clásico del lab)
+    pub fn single(mesh: Mesh, kind: ShaderKind) -> Self {
+        let mut scene = Self::new();
+        let m = scene.add_mesh(mesh);
+        let mut body = Node::new(kind.name(), kind, m);
+        body.transform = Transform::uniform(1.2);
+        scene.add_planet(body, PlanetParams::default());
+        scene
+    }
+
+    /// Sistema de ejemplo: estrella central con planetas en órbita,
+    /// un gigante gaseoso con anillos y un planeta helado con luna
+    pub fn solar_system(mesh: Mesh) -> Self {
+        let mut scene = Self::new();
+        let m = scene.add_mesh(mesh);
+
+        let mut sun = Node::new("sol", ShaderKind::Star, m);
+        sun.transform = Transform::uniform(2.4);
+        let sun = scene.add_planet(sun, PlanetParams::default());
+
+        // nombre, shader, radio orbital, tamaño, velocidad (rad/s), fase (grados)
+        let planets = [
+            ("roca",   ShaderKind::Rocky, 3.2,  0.45, 0.50, 0.0,   PlanetParams { has_atmosphere: true, ..PlanetParams::default() }),
+            ("lava",   ShaderKind::Lava,  4.6,  0.55, 0.36, 120.0, PlanetParams::default()),
+            ("gas",    ShaderKind::Gas,   7.0,  1.00, 0.22, 229.0, PlanetParams { has_rings: true, has_atmosphere: true, ..PlanetParams::default() }),
+            ("hielo",  ShaderKind::Ice,   9.6,  0.65, 0.15, 57.0,  PlanetParams { has_moon: true, has_atmosphere: true, has_clouds: true, ..PlanetParams::default() }),
+            ("tierra", ShaderKind::Ocean, 12.0, 0.60, 0.11, 298.0, PlanetParams { has_atmosphere: true, has_clouds: true, ..PlanetParams::default() }),
+            ("scifi",  ShaderKind::SciFi, 14.5, 0.60, 0.09, 170.0, PlanetParams::default()),
+        ];
+        for (name, kind, radius, size, speed, phase, params) in planets {
+            let mut p = Node::new(name, kind, m);
+            p.parent = Some(sun);
+            p.transform = Transform::uniform(size);
+            p.orbit = Some(Orbit { radius, speed, phase: f32::to_radians(phase), inclination: 0.0 });
+            scene.add_planet(p, params);
+        }
+        scene
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moon_follows_planet_around_star() {
+        let mut scene = Scene::new();
+        let m = scene.add_mesh(Mesh::default());
+        let star = scene.add_node(Node::new("sol", ShaderKind::Star, m));
+        let mut planet = Node::new("p", ShaderKind::Rocky, m);
+        planet.parent = Some(star);
+        planet.orbit = Some(Orbit::new(5.0, 1.0));
+        let planet = scene.add_planet(planet, PlanetParams { has_moon: true, ..PlanetParams::default() });
+        let moon = scene.find("p-luna").unwrap();
+        assert_eq!(scene.nodes[moon].parent, Some(planet));
+
+        let pos = |m: &Mat4| Vec3::new(m.m[0][3], m.m[1][3], m.m[2][3]);
+        for t in [0.0, 1.0, 2.5] {
+            let w = scene.world_transforms(t);
+            assert!((pos(&w[planet]).length() - 5.0).abs() < 1e-4);
+            assert!(((pos(&w[moon]) - pos(&w[planet])).length() - 2.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn every_star_lights_the_others() {
+        use crate::shaders::common::lambert;
+        let mut scene = Scene::new();
+        let m = scene.add_mesh(Mesh::default());
+        let a = scene.add_node(Node::new("a", ShaderKind::Star, m));
+        let mut b = Node::new("b", ShaderKind::Star, m);
+        b.transform.translation = Vec3::new(10.0, 0.0, 0.0);
+        let b = scene.add_node(b);
+        let mut p = Node::new("p", ShaderKind::Rocky, m);
+        p.transform.translation = Vec3::new(5.0, 0.0, 0.0);
+        let p = scene.add_node(p);
+
+        let models = scene.world_transforms(0.0);
+        let base = Uniforms::default();
+        let lights = scene.frame_lights(&ShaderLibrary::new(), &models, &base);
+        let u = scene.node_uniforms(p, &models[p], &lights, &base);
+        assert_eq!(u.lights.len(), 2);
+        // El planeta recibe luz por ambos lados, cada uno desde su estrella
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        assert!(lambert(Vec3::new(4.0, 0.0, 0.0), -x, &u).x > 0.9);
+        assert!(lambert(Vec3::new(6.0, 0.0, 0.0), x, &u).x > 0.9);
+        assert_eq!(lambert(Vec3::new(5.0, 1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), &u).x, 0.0);
+        // Una estrella no se ilumina a sí misma
+        let ua = scene.node_uniforms(a, &models[a], &lights, &base);
+        assert_eq!(ua.lights.as_slice(), &[lights[b].1]);
+    }
+
+    #[test]
+    fn star_temperature_tints_planet_light() {
+        use crate::shaders::blackbody::SUN_KELVIN;
+        let mut scene = Scene::solar_system(Mesh::default());
+        let sun = scene.find_kind(ShaderKind::Star).unwrap();
+        let models = scene.world_transforms(0.0);
+        let shaders = ShaderLibrary::new();
+        let mut light_at = |kelvin: f32| {
+            scene.nodes[sun].star.temperature = kelvin;
+            let (src, l) = scene.frame_lights(&shaders, &models, &Uniforms::default())[0];
+            assert_eq!(src, Some(sun));
+            l
+        };
+        let (cold, warm, hot) = (light_at(3000.0), light_at(SUN_KELVIN), light_at(20000.0));
+        // Fría: más roja y tenue; caliente: más azul y brillante; la del Sol es ~blanca
+        assert!(cold.color.z / cold.color.x < warm.color.z / warm.color.x);
+        assert!(warm.color.z / warm.color.x < hot.color.z / hot.color.x);
+        assert!(cold.intensity < warm.intensity && warm.intensity < hot.intensity);
+        assert!((warm.intensity - 1.0).abs() < 1e-4 && warm.color.y.min(warm.color.z) > 0.8, "{:?}", warm.color);
+    }
+
+    #[test]
+    fn transparent_draws_go_last_farthest_first() {
+        use crate::math::mat::look_at_rh;
+        let scene = Scene::solar_system(Mesh::default());
+        let shaders = ShaderLibrary::new();
+        let base = Uniforms { view: look_at_rh(Vec3::new(0.0, 5.0, 15.0), Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0)), ..Uniforms::default() };
+        let mut queue = RenderQueue::new();
+        scene.enqueue(&shaders, &base, &mut queue);
+        let order = queue.sorted();
+        assert_eq!(order.len(), queue.len());
+
+        let split = order.iter().position(|c| c.state.is_transparent()).unwrap();
+        assert!(split > 0 && order[split..].iter().all(|c| c.state.is_transparent()));
+        let depth = |u: &Uniforms| (u.view * u.model * Vec4::new(0.0, 0.0, 0.0, 1.0)).z;
+        let depths: Vec<f32> = order[split..].iter().map(|c| depth(&c.uniforms)).collect();
+        assert!(depths.windows(2).all(|w| w[0] <= w[1]), "{:?}", depths);
+    }
+
+    #[test]
+    fn atmosphere_takes_the_radius_of_its_body_mesh() {
+        use crate::scene::model::Vertex;
+        let axes = [Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, -2.0)];
+        let mesh = Mesh { vertices: axes.iter().map(|&pos| Vertex { pos, ..Vertex::default() }).collect(), indices: Vec::new() };
+        let mut scene = Scene::single(mesh, ShaderKind::Rocky);
+        scene.toggle_child(0, ShaderKind::Atmosphere);
+        let atm = scene.child_of_kind(0, ShaderKind::Atmosphere).unwrap();
+        let models = scene.world_transforms(0.0);
+        let u = scene.node_uniforms(atm, &models[atm], &[], &Uniforms::default());
+        assert!((u.mesh_radius - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn corona_follows_the_star_shader() {
+        let mut scene = Scene::single(Mesh::default(), ShaderKind::Rocky);
+        assert_eq!(scene.child_of_kind(0, ShaderKind::Corona), None);
+        scene.set_shader(0, ShaderKind::Star);
+        let corona = scene.child_of_kind(0, ShaderKind::Corona).unwrap();
+        assert!(scene.nodes[corona].visible);
+        assert!((scene.nodes[corona].transform.scale.x - 1.2 * corona::SHELL).abs() < 1e-5);
+        assert_eq!(scene.bodies(), vec![0]);
+
+        // Usa los parámetros actuales de su estrella
+        scene.nodes[0].star.temperature = 3000.0;
+        let models = scene.world_transforms(0.0);
+        let u = scene.node_uniforms(corona, &models[corona], &[], &Uniforms::default());
+        assert_eq!(u.star.temperature, 3000.0);
+
+        scene.set_shader(0, ShaderKind::Gas);
+        assert!(!scene.nodes[corona].visible);
+        scene.set_shader(0, ShaderKind::Star);
+        assert_eq!(scene.child_of_kind(0, ShaderKind::Corona), Some(corona));
+        assert!(scene.nodes[corona].visible);
+    }
+
+    #[test]
+    fn toggle_rings_keeps_node_ids() {
+        let mut scene = Scene::single(Mesh::default(), ShaderKind::Gas);
+        assert!(scene.toggle_child(0, ShaderKind::Rings));
+        let rings = scene.child_of_kind(0, ShaderKind::Rings).unwrap();
+        assert!(scene.nodes[0].planet.has_rings);
+        assert!(!scene.toggle_child(0, ShaderKind::Rings));
+        assert_eq!(scene.child_of_kind(0, ShaderKind::Rings), Some(rings));
+        assert!(!scene.nodes[rings].visible);
+        assert_eq!(scene.bodies(), vec![0]);
+    }
+}
@@ -21,9 +21,13 @@ pub enum Action {
     Shader4,
     Shader5,
     Shader6,
+    Shader7,
     ToggleRings,
     ToggleMoon,
+    ToggleAtmosphere,
+    ToggleClouds,
     PauseRotation,
+    NextBody,          // Seleccionar el siguiente cuerpo de la escena
 
     // Tuning de estrella
     TempInc,           // Aumentar temperatura
@@ -41,6 +45,11 @@ pub enum Action {
     NoiseCellular,     // Cambiar a Cellular
     ToggleCellularFlares, // Toggle cellular para flares
 
+    // Post-proceso
+    CycleTonemap,      // Alternar operador de tonemapping
+    BloomInc,          // Aumentar intensidad del bloom
+    BloomDec,          // Disminuir intensidad del bloom
+
     // Utilidad
     Screenshot,
     Quit,
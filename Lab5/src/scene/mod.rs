@@ -1,7 +1,13 @@
+//! Carga de mallas `.obj`, grafo de escena (y archivos TOML), cámara free-fly e input por acciones.
+
 pub mod model;
 pub mod camera;
 pub mod input;
+pub mod graph;
+pub mod file;
 
 pub use model::{Mesh, load_obj};
 pub use camera::Camera;
 pub use input::{Input, Action};
+pub use graph::{CLEAR_COLOR, Scene, Node, NodeId, Transform, Orbit, TextureSlots};
+pub use file::SceneFile;
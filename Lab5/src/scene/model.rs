@@ -1,11 +1,12 @@
 use std::{fs::File, io::{BufRead, BufReader}};
-use crate::math::{Vec3};
+use crate::math::{Vec2, Vec3};
 
-/// Un vertice con posición y normal 
+/// Un vertice con posición, normal y coordenada de textura
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Vertex {
     pub pos: Vec3,
     pub nrm: Vec3,
+    pub uv: Vec2,
 }
 
 /// Triángulo indexado
@@ -26,6 +27,12 @@ pub struct Mesh {
 impl Mesh {
     pub fn is_empty(&self) -> bool { self.vertices.is_empty() || self.indices.is_empty() }
 
+    /// Distancia media de los vértices al origen (0 sin vértices): el radio de una esfera
+    pub fn mean_radius(&self) -> f32 {
+        if self.vertices.is_empty() { return 0.0; }
+        self.vertices.iter().map(|v| v.pos.length()).sum::<f32>() / self.vertices.len() as f32
+    }
+
     pub fn recompute_normals(&mut self) {
         // Inicializa en cero
         for v in &mut self.vertices { v.nrm = Vec3::ZERO; }
@@ -44,9 +51,9 @@ impl Mesh {
     }
 }
 
-/// Carga un .obj **sin materiales/uvs** con formato de cara `v//vn` o `v`
-/// - Soporta: `v x y z`, `vn x y z`, `f a//na b//nb c//nc` (triangulado)
-/// - Si no hay `vn`, recalcula normales.
+/// Carga un .obj **sin materiales** con caras `v`, `v/vt`, `v//vn` o `v/vt/vn`
+/// - Soporta: `v x y z`, `vt u v`, `vn x y z`, `f a/ta/na ...` (triangulado)
+/// - Si no hay `vn`, recalcula normales; si no hay `vt`, uv = (0,0).
 /// - Indices de .obj son 1-based (positivos). No soporta negativos.
 pub fn load_obj(path: &str) -> Result<Mesh, String> {
     let file = File::open(path).map_err(|e| format!("No pude abrir {}: {}", path, e))?;
@@ -54,12 +61,13 @@ pub fn load_obj(path: &str) -> Result<Mesh, String> {
 
     let mut positions: Vec<Vec3> = Vec::new();
     let mut normals:   Vec<Vec3> = Vec::new();
+    let mut uvs:       Vec<Vec2> = Vec::new();
     let mut vertices: Vec<Vertex> = Vec::new();
     let mut indices:  Vec<Triangle> = Vec::new();
 
     use std::collections::HashMap;
     #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
-    struct Key { v: u32, n: i32 } 
+    struct Key { v: u32, t: i32, n: i32 } 
     let mut dedup: HashMap<Key, u32> = HashMap::new();
 
     for line in reader.lines() {
@@ -75,6 +83,13 @@ pub fn load_obj(path: &str) -> Result<Mesh, String> {
             let y: f32 = it.next().ok_or("v incompleto")?.parse().map_err(|_|"v.y inválido")?;
             let z: f32 = it.next().ok_or("v incompleto")?.parse().map_err(|_|"v.z inválido")?;
             positions.push(Vec3::new(x,y,z));
+        } else if s.starts_with("vt ") {
+            // vt u v (se ignora una tercera coordenada)
+            let mut it = s.split_whitespace();
+            it.next(); // "vt"
+            let u: f32 = it.next().ok_or("vt incompleto")?.parse().map_err(|_|"vt.u inválido")?;
+            let v: f32 = it.next().map_or(Ok(0.0), |t| t.parse().map_err(|_|"vt.v inválido"))?;
+            uvs.push(Vec2::new(u, v));
         } else if s.starts_with("vn ") {
             // vn x y z
             let mut it = s.split_whitespace();
@@ -88,11 +103,11 @@ pub fn load_obj(path: &str) -> Result<Mesh, String> {
             let parts: Vec<&str> = s.split_whitespace().collect();
             if parts.len() < 4 { return Err(format!("Cara inválida: {}", s)); }
 
-            // Convierte cada “token de vértice” a (v_idx, vn_idx|-1)
+            // Convierte cada “token de vértice” a (v_idx, vt_idx|-1, vn_idx|-1)
             let mut face_idx: Vec<u32> = Vec::new(); 
             for p in &parts[1..] {
-                let (v_i, vn_i_opt) = parse_face_token(p)?;
-                let key = Key{ v: v_i, n: vn_i_opt.unwrap_or(-1) };
+                let (v_i, vt_i_opt, vn_i_opt) = parse_face_token(p)?;
+                let key = Key{ v: v_i, t: vt_i_opt.unwrap_or(-1), n: vn_i_opt.unwrap_or(-1) };
                 let idx = if let Some(&found) = dedup.get(&key) {
                     found
                 } else {
@@ -106,8 +121,14 @@ pub fn load_obj(path: &str) -> Result<Mesh, String> {
                     } else {
                         Vec3::ZERO 
                     };
+                    let uv = if let Some(vt_i) = vt_i_opt {
+                        *uvs.get((vt_i-1) as usize)
+                            .ok_or_else(|| format!("Índice vt fuera de rango en {}", s))?
+                    } else {
+                        Vec2::ZERO
+                    };
                     let new_index = vertices.len() as u32;
-                    vertices.push(Vertex{ pos: *pos, nrm });
+                    vertices.push(Vertex{ pos: *pos, nrm, uv });
                     dedup.insert(key, new_index);
                     new_index
                 };
@@ -132,37 +153,24 @@ pub fn load_obj(path: &str) -> Result<Mesh, String> {
     Ok(mesh)
 }
 
-/// Parsea un token de cara:
-/// - "a//na" -> (a, Some(na))
-/// - "a"     -> (a, None)
-/// - "a/b/c" o "a/b" los rechazamos para mantener sencillo (no hay vt)
-// Acepta: "a/b/c" (v/vt/vn), "a//c" (v//vn), "a/b" (v/vt), "a" (v)
-// Ignora vt; usa vn si está.
-fn parse_face_token(tok: &str) -> Result<(u32, Option<i32>), String> {
+/// Parsea un token de cara (índices 1-based):
+/// - "a"     -> (a, None, None)
+/// - "a/b"   -> (a, Some(b), None)
+/// - "a//c"  -> (a, None, Some(c))
+/// - "a/b/c" -> (a, Some(b), Some(c))
+fn parse_face_token(tok: &str) -> Result<(u32, Option<i32>, Option<i32>), String> {
     let parts: Vec<&str> = tok.split('/').collect();
-    match parts.len() {
-        1 => {
-            // "a"
-            let v: u32 = parts[0].parse().map_err(|_| format!("v inválido en '{}'", tok))?;
-            Ok((v, None))
-        }
-        2 => {
-            // "a/b"  (sin vn)
-            let v: u32 = parts[0].parse().map_err(|_| format!("v inválido en '{}'", tok))?;
-            Ok((v, None))
-        }
-        3 => {
-            // "a/b/c" o "a//c"
-            let v: u32 = parts[0].parse().map_err(|_| format!("v inválido en '{}'", tok))?;
-            let vn_opt = if parts[2].is_empty() {
-                None
-            } else {
-                Some(parts[2].parse::<i32>().map_err(|_| format!("vn inválido en '{}'", tok))?)
-            };
-            Ok((v, vn_opt))
-        }
-        _ => Err(format!("Token de cara no soportado: '{}'", tok)),
+    if parts.len() > 3 {
+        return Err(format!("Token de cara no soportado: '{}'", tok));
     }
+    let v: u32 = parts[0].parse().map_err(|_| format!("v inválido en '{}'", tok))?;
+    let opt = |i: usize, name: &str| -> Result<Option<i32>, String> {
+        match parts.get(i) {
+            Some(p) if !p.is_empty() => p.parse().map(Some).map_err(|_| format!("{} inválido en '{}'", name, tok)),
+            _ => Ok(None),
+        }
+    };
+    Ok((v, opt(1, "vt")?, opt(2, "vn")?))
 }
 
 // Tests
@@ -171,8 +179,11 @@ mod tests {
     use super::*;
     #[test]
     fn parse_faces_basic() {
-        assert_eq!(parse_face_token("3//7").unwrap(), (3, Some(7)));
-        assert_eq!(parse_face_token("12").unwrap(), (12, None));
-        assert!(parse_face_token("1/2/3").is_err());
+        assert_eq!(parse_face_token("3//7").unwrap(), (3, None, Some(7)));
+        assert_eq!(parse_face_token("12").unwrap(), (12, None, None));
+        assert_eq!(parse_face_token("1/2/3").unwrap(), (1, Some(2), Some(3)));
+        assert_eq!(parse_face_token("4/5").unwrap(), (4, Some(5), None));
+        assert!(parse_face_token("1/2/3/4").is_err());
+        assert!(parse_face_token("x//1").is_err());
     }
 }
@@ -0,0 +1,189 @@
+//! Atmósfera: dispersión de Rayleigh y Mie integrada por ray marching en una cáscara
+//! alrededor del planeta (hijo `Atmosphere` del nodo planeta).
+//!
+//! Se dibujan las caras delanteras de la cáscara con mezcla premultiplicada: la luz
+//! dispersada hacia la cámara se suma y lo que hay detrás (el planeta o el fondo) se
+//! atenúa con la transmitancia del rayo. Cada muestra recibe las luces que el propio
+//! planeta no tapa, así el lado de noche queda oscuro y el terminador se enrojece.
+
+use crate::math::{Vec3, Vec4};
+use crate::renderer::{uniforms::{AtmosphereParams, Uniforms}, pipeline::{Shader, VertexIn, VertexOut}};
+use super::common::*;
+
+// Pasos por pixel a lo largo del rayo y hacia cada luz
+const VIEW_STEPS: usize = 16;
+const LIGHT_STEPS: usize = 6;
+// Extinción de Mie = dispersión + un poco de absorción
+const MIE_EXTINCTION: f32 = 1.1;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Atmosphere;
+
+impl Atmosphere {
+    /// Radio de la cáscara respecto al del planeta
+    pub fn shell_scale(a: &AtmosphereParams) -> f32 {
+        1.0 + a.height / a.planet_radius.max(1e-3)
+    }
+
+    /// Luz dispersada hacia `origin` y transmitancia del rayo `origin + t·dir` (en km,
+    /// con el planeta en el origen). `lights` son pares (dirección hacia la luz, radiancia);
+    /// las luces están lejos comparadas con el planeta, así que la dirección es la misma
+    /// en toda la capa.
+    pub fn scatter(origin: Vec3, dir: Vec3, lights: &[(Vec3, Vec3)], a: &AtmosphereParams) -> (Vec3, Vec3) {
+        let none = (Vec3::ZERO, Vec3::from_scalar(1.0));
+        let (rp, ra) = (a.planet_radius, a.planet_radius + a.height);
+        let Some((t0, mut t1)) = sphere_hit(origin, dir, ra) else { return none };
+        let t0 = t0.max(0.0);
+        if let Some((p0, _)) = sphere_hit(origin, dir, rp) && p0 > 0.0 {
+            t1 = t1.min(p0);
+        }
+        if t1 <= t0 { return none; }
+
+        let extinction = |od_r: f32, od_m: f32| exp3(-(a.rayleigh * od_r + Vec3::from_scalar(a.mie * MIE_EXTINCTION * od_m)));
+        let density = |p: Vec3| {
+            let h = p.length() - rp;
+            ((-h / a.rayleigh_height).exp(), (-h / a.mie_height).exp())
+        };
+
+        let ds = (t1 - t0) / VIEW_STEPS as f32;
+        let (mut od_r, mut od_m) = (0.0f32, 0.0f32);
+        let (mut sum_r, mut sum_m) = (Vec3::ZERO, Vec3::ZERO);
+        for i in 0..VIEW_STEPS {
+            let p = origin + dir * (t0 + (i as f32 + 0.5) * ds);
+            let (dr, dm) = density(p);
+            od_r += dr * ds;
+            od_m += dm * ds;
+            for &(l, radiance) in lights {
+                // Sombra del propio planeta
+                if matches!(sphere_hit(p, l, rp), Some((t, _)) if t > 0.0) { continue; }
+                let Some((_, exit)) = sphere_hit(p, l, ra) else { continue };
+                let ls = exit / LIGHT_STEPS as f32;
+                let (mut lr, mut lm) = (0.0f32, 0.0f32);
+                for j in 0..LIGHT_STEPS {
+                    let (r, m) = density(p + l * ((j as f32 + 0.5) * ls));
+                    lr += r * ls;
+                    lm += m * ls;
+                }
+                let light = extinction(od_r + lr, od_m + lm).hadamard(radiance);
+                let mu = dir.dot(l);
+                sum_r += light * (dr * ds * rayleigh_phase(mu));
+                sum_m += light * (dm * ds * mie_phase(mu, a.mie_g));
+            }
+        }
+        (sum_r.hadamard(a.rayleigh) + sum_m * a.mie, extinction(od_r, od_m))
+    }
+}
+
+impl Shader for Atmosphere {
+    fn name(&self) -> &'static str { "Atmosphere" }
+
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+        // Vértices sobre la esfera exacta del radio de la malla (un poco más grande, para
+        // que las caras planas cubran toda la cáscara que se integra)
+        let p = vin.pos.normalize() * (u.mesh_radius * 1.02);
+        let clip = u.proj * u.view * u.model * Vec4::from3(p, 1.0);
+        let pos_ws = (u.model * Vec4::from3(p, 1.0)).xyz();
+        let nrm_ws = (u.model * Vec4::from3(vin.nrm, 0.0)).xyz();
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
+    }
+
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
+        let a = &u.planet.atmosphere;
+        // km por unidad de mundo: la cáscara mide `planet_radius + height`
+        let km = (a.planet_radius + a.height) / (model_scale(u) * u.mesh_radius).max(1e-6);
+        let center = Vec3::new(u.model.m[0][3], u.model.m[1][3], u.model.m[2][3]);
+
+        let mut lights = [(Vec3::ZERO, Vec3::ZERO); crate::renderer::MAX_LIGHTS];
+        let mut n = 0;
+        for l in u.lights.iter() {
+            let (dir, radiance) = l.incident(center);
+            lights[n] = (dir, radiance);
+            n += 1;
+        }
+
+        let origin = object_pos(u.camera_pos, u) * km;
+        let dir = (vary.pos_ws - u.camera_pos).normalize();
+        let (col, transmittance) = Atmosphere::scatter(origin, dir, &lights[..n], a);
+        // La mezcla sólo admite un alpha: se usa la transmitancia de la luminancia.
+        // Ya en RGB lineal (y premultiplicado): no pasa por `to_color_alpha`
+        let alpha = 1.0 - transmittance.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+        Vec4::from3(col, alpha)
+    }
+}
+
+/// Intersecciones (entrada, salida) del rayo `o + t·d` con la esfera de radio `r` en el origen
+fn sphere_hit(o: Vec3, d: Vec3, r: f32) -> Option<(f32, f32)> {
+    let b = o.dot(d);
+    let disc = b * b - (o.dot(o) - r * r);
+    if disc <= 0.0 { return None; }
+    let s = disc.sqrt();
+    Some((-b - s, -b + s))
+}
+
+fn exp3(v: Vec3) -> Vec3 { Vec3::new(v.x.exp(), v.y.exp(), v.z.exp()) }
+
+fn rayleigh_phase(mu: f32) -> f32 {
+    3.0 / (16.0 * std::f32::consts::PI) * (1.0 + mu * mu)
+}
+
+/// Henyey-Greenstein
+fn mie_phase(mu: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    (1.0 - g2) / (4.0 * std::f32::consts::PI * (1.0 + g2 - 2.0 * g * mu).max(1e-4).powf(1.5))
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limb_is_blue_terminator_red_night_dark() {
+        let a = AtmosphereParams::default();
+        let sun = [(Vec3::new(1.0, 0.0, 0.0), Vec3::from_scalar(1.0))];
+        let eye = Vec3::new(0.0, 0.0, 20000.0);
+        let toward = |p: Vec3| (p - eye).normalize();
+        let r = a.planet_radius + 20.0;
+
+        // Borde iluminado del disco visto de frente a la luz: domina el azul
+        let (day, t_day) = Atmosphere::scatter(eye, toward(Vec3::new(0.0, r, 0.0)), &[(Vec3::new(0.0, 0.0, 1.0), Vec3::from_scalar(1.0))], &a);
+        assert!(day.z > day.x, "{:?}", day);
+        assert!(t_day.z < t_day.x);
+
+        // Luz rasante en el terminador: la luz que llega ya perdió el azul
+        let (term, _) = Atmosphere::scatter(eye, toward(Vec3::new(0.0, a.planet_radius + 2.0, 0.0)), &sun, &a);
+        assert!(term.x / term.z > day.x / day.z, "{:?} vs {:?}", term, day);
+
+        // Del lado de noche (detrás del planeta respecto a la luz) no hay nada
+        let (night, _) = Atmosphere::scatter(eye, toward(Vec3::new(-r * 0.7, r * 0.7, 0.0)), &sun, &a);
+        assert_eq!(night, Vec3::ZERO);
+
+        // Un rayo que no toca la cáscara pasa intacto
+        let (miss, t_miss) = Atmosphere::scatter(eye, toward(Vec3::new(0.0, 2.0 * r, 0.0)), &sun, &a);
+        assert_eq!((miss, t_miss), (Vec3::ZERO, Vec3::from_scalar(1.0)));
+    }
+
+    #[test]
+    fn orange_star_keeps_its_hue_in_lambert_and_scatter() {
+        use crate::renderer::{Light, Lights, uniforms::{StarParams, Uniforms}};
+        use crate::shaders::star::Star;
+        let (color, lum) = Star::light(&StarParams { temperature: 3500.0, ..StarParams::default() }, 0.0);
+        let dir = Vec3::new(0.0, 0.0, 1.0);
+        let u = Uniforms { lights: Lights::single(Light::directional(dir, color, lum)), ..Uniforms::default() };
+
+        // Lambert trabaja en sRGB: `to_color` lo devuelve a lineal
+        let diffuse = to_color(lambert(Vec3::ZERO, dir, &u)).xyz();
+        // Atmósfera sólo con Mie (gris): la luz dispersada tiene el color de la luz
+        let a = AtmosphereParams { rayleigh: Vec3::ZERO, ..AtmosphereParams::default() };
+        let eye = Vec3::new(0.0, 0.0, 20000.0);
+        let r = a.planet_radius + 20.0;
+        let (sky, _) = Atmosphere::scatter(eye, (Vec3::new(0.0, r, 0.0) - eye).normalize(), &[(dir, color * lum)], &a);
+
+        let hue = |c: Vec3| Vec3::new(c.x / c.y, 1.0, c.z / c.y);
+        assert!(color.x > color.y && color.y > color.z, "{:?}", color);
+        // Lambert escala en sRGB, que no es una potencia exacta: igual hasta ~1%. Con la
+        // luz guardada en sRGB la atmósfera daba r/g ≈ 1.3 en vez de ≈ 1.7
+        let (d, s) = (hue(diffuse), hue(sky));
+        assert!((d.x / s.x - 1.0).abs() < 0.02 && (d.z / s.z - 1.0).abs() < 0.03, "{:?} vs {:?}", d, s);
+    }
+}
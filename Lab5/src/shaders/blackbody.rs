@@ -0,0 +1,117 @@
+//! Color de cuerpo negro: temperatura (Kelvin) -> RGB lineal (sRGB/Rec.709, D65).
+//!
+//! Se integra el espectro de Planck contra las funciones de igualación CIE 1931 (2°)
+//! y se pasa de XYZ a RGB. Las CIE usan el ajuste multi-lóbulo gaussiano de Wyman,
+//! Sloan y Shirley (2013), así no hace falta tabular los datos del estándar.
+
+use std::sync::OnceLock;
+use crate::math::Vec3;
+
+/// Rango soportado (enanas rojas frías a gigantes azules)
+pub const MIN_KELVIN: f32 = 1000.0;
+pub const MAX_KELVIN: f32 = 40000.0;
+/// Temperatura efectiva del Sol
+pub const SUN_KELVIN: f32 = 5772.0;
+
+// Segunda constante de radiación c2 = h·c/k, en nm·K
+const C2: f64 = 1.438_776_9e7;
+
+/// Funciones de igualación CIE 1931 (x̄, ȳ, z̄) en `lambda` nm
+pub fn cie_xyz_bar(lambda: f64) -> Vec3 {
+    // Gaussiana con anchos distintos a cada lado del pico
+    let g = |mu: f64, s1: f64, s2: f64| {
+        let t = (lambda - mu) / if lambda < mu { s1 } else { s2 };
+        (-0.5 * t * t).exp()
+    };
+    let x = 1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2);
+    let y = 0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1);
+    let z = 1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8);
+    Vec3::new(x as f32, y as f32, z as f32)
+}
+
+/// Radiancia espectral de Planck en `lambda` nm, sin las constantes (sólo importa la forma)
+pub fn planck(lambda: f64, kelvin: f64) -> f64 {
+    lambda.powi(-5) / ((C2 / (lambda * kelvin)).exp_m1())
+}
+
+/// XYZ del cuerpo negro, normalizado a Y = 1
+pub fn blackbody_xyz(kelvin: f32) -> Vec3 {
+    let t = kelvin.clamp(MIN_KELVIN, MAX_KELVIN) as f64;
+    let (mut x, mut y, mut z) = (0.0f64, 0.0f64, 0.0f64);
+    // 380..780 nm cada 5 nm
+    for i in 0..=80 {
+        let l = 380.0 + 5.0 * i as f64;
+        let b = planck(l, t);
+        let c = cie_xyz_bar(l);
+        x += b * c.x as f64;
+        y += b * c.y as f64;
+        z += b * c.z as f64;
+    }
+    Vec3::new((x / y) as f32, 1.0, (z / y) as f32)
+}
+
+/// XYZ -> RGB lineal (primarios sRGB, blanco D65)
+pub fn xyz_to_linear_srgb(c: Vec3) -> Vec3 {
+    Vec3::new(
+        3.2406 * c.x - 1.5372 * c.y - 0.4986 * c.z,
+        -0.9689 * c.x + 1.8758 * c.y + 0.0415 * c.z,
+        0.0557 * c.x - 0.2040 * c.y + 1.0570 * c.z,
+    )
+}
+
+/// Color del cuerpo negro en RGB lineal, integrando el espectro. Sólo cromaticidad:
+/// el canal mayor vale 1 (lo que queda fuera del gamut sRGB se recorta a 0).
+pub fn blackbody_exact(kelvin: f32) -> Vec3 {
+    let c = xyz_to_linear_srgb(blackbody_xyz(kelvin));
+    let c = Vec3::new(c.x.max(0.0), c.y.max(0.0), c.z.max(0.0));
+    c / c.x.max(c.y).max(c.z)
+}
+
+// Tabla en escala logarítmica de temperatura (el color cambia rápido en K bajos)
+const TABLE_SIZE: usize = 256;
+
+fn table() -> &'static [Vec3] {
+    static TABLE: OnceLock<Vec<Vec3>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        (0..TABLE_SIZE)
+            .map(|i| blackbody_exact(kelvin_at(i as f32 / (TABLE_SIZE - 1) as f32)))
+            .collect()
+    })
+}
+
+fn kelvin_at(t: f32) -> f32 {
+    MIN_KELVIN * (MAX_KELVIN / MIN_KELVIN).powf(t)
+}
+
+/// Como `blackbody_exact` pero interpolando una tabla precalculada (para usar por fragment)
+pub fn blackbody(kelvin: f32) -> Vec3 {
+    let k = kelvin.clamp(MIN_KELVIN, MAX_KELVIN);
+    let t = (k / MIN_KELVIN).ln() / (MAX_KELVIN / MIN_KELVIN).ln() * (TABLE_SIZE - 1) as f32;
+    let i = (t as usize).min(TABLE_SIZE - 2);
+    let f = t - i as f32;
+    let tab = table();
+    tab[i] * (1.0 - f) + tab[i + 1] * f
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blackbody_goes_from_red_to_blue() {
+        // Cerca de 6500 K el cuerpo negro es casi el blanco D65
+        let w = blackbody_exact(6500.0);
+        assert!(w.x > 0.9 && w.y > 0.9 && w.z > 0.9, "{:?}", w);
+        // Enana roja: r > g > b; gigante azul: b domina
+        let red = blackbody_exact(3000.0);
+        assert!(red.x == 1.0 && red.y < 0.8 && red.z < red.y, "{:?}", red);
+        let blue = blackbody_exact(20000.0);
+        assert!(blue.z == 1.0 && blue.x < 0.8, "{:?}", blue);
+        // La tabla sigue al resultado exacto
+        for k in [1500.0, SUN_KELVIN, 12345.0, 38000.0] {
+            let (a, b) = (blackbody(k), blackbody_exact(k));
+            assert!((a - b).length() < 0.01, "{} K: {:?} vs {:?}", k, a, b);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Capa de nubes: una esfera un poco más grande que el planeta (hijo `Clouds`) que se
+//! mezcla con alpha sobre la superficie y gira con su propio viento.
+//!
+//! La forma sale de un FBM simplex con el dominio deformado por otro FBM, que además se corre con
+//! el tiempo, así las nubes cambian mientras avanzan. Las mismas nubes oscurecen la
+//! superficie (`Clouds::shadow`): la luz principal se corta donde su rayo cruza la capa.
+
+use crate::math::{Vec3, Vec4};
+use crate::renderer::{uniforms::{CloudParams, Uniforms}, pipeline::{Shader, VertexIn, VertexOut}};
+use super::common::*;
+use super::noise::NoiseType;
+
+// Octavas de las nubes y de su sombra (menos: bordes más suaves)
+const OCTAVES: i32 = 5;
+const SHADOW_OCTAVES: i32 = 3;
+// Cuánta luz quita una nube opaca a la superficie
+const SHADOW_STRENGTH: f32 = 0.7;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Clouds;
+
+impl Clouds {
+    /// Opacidad de las nubes en la dirección `d` (espacio propio de la capa) en el tiempo `t`
+    pub fn opacity(d: Vec3, t: f32, c: &CloudParams, octaves: i32) -> f32 {
+        if c.coverage <= 0.0 { return 0.0; }
+        let q = d * c.scale;
+        // Deformación del dominio: el ruido se evalúa en un punto corrido por otro ruido
+        let drift = Vec3::new(t * 0.010, 0.0, -t * 0.013);
+        let warp = |o: Vec3| fbm_3d_type(q + o + drift, 3, 2.0, 0.5, 1.0, NoiseType::Simplex) - 0.5;
+        let w = Vec3::new(warp(Vec3::new(1.7, 9.2, 3.1)), warp(Vec3::new(8.3, 2.8, 5.4)), warp(Vec3::new(4.1, 6.6, 7.9)));
+        let n = fbm_3d_type(q + w * 1.5, octaves, 2.0, 0.5, 1.0, NoiseType::Simplex);
+        // El fbm simplex cae casi siempre en [0.3, 0.7]: se lleva a [0, 1]
+        let n = saturate((n - 0.3) / 0.4);
+        let edge = 1.0 - c.coverage;
+        smoothstep(edge - 0.12, edge + 0.12, n) * c.density
+    }
+
+    /// Luz de la luz principal que dejan pasar las nubes hasta el punto `p` de la
+    /// superficie (1 sin nubes). Usa menos octavas, así la sombra queda difusa.
+    pub fn shadow(p: Vec3, u: &Uniforms) -> f32 {
+        if !u.planet.has_clouds { return 1.0; }
+        let Some(main) = u.lights.main() else { return 1.0 };
+        let c = &u.planet.clouds;
+        let (l, _) = main.incident(p);
+        // Donde el rayo hacia la luz sale de la capa (la malla de nubes es la del planeta
+        // escalada, así que la capa queda a `altitude` sobre este punto)
+        let o = object_pos(p, u);
+        let r = o.length() * (1.0 + c.altitude);
+        let b = o.dot(l);
+        let t = -b + (b * b - (o.dot(o) - r * r)).max(0.0).sqrt();
+        let d = body_dir(p + l * t, u, u.time * c.wind_speed);
+        1.0 - SHADOW_STRENGTH * Self::opacity(d, u.time, c, SHADOW_OCTAVES)
+    }
+}
+
+impl Shader for Clouds {
+    fn name(&self) -> &'static str { "Clouds" }
+
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+        let clip = u.proj * u.view * u.model * Vec4::from3(vin.pos, 1.0);
+        let pos_ws = (u.model * Vec4::from3(vin.pos, 1.0)).xyz();
+        let nrm_ws = (u.model * Vec4::from3(vin.nrm, 0.0)).xyz();
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
+    }
+
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
+        let c = &u.planet.clouds;
+        let d = body_dir(vary.pos_ws, u, u.time * c.wind_speed);
+        let alpha = Self::opacity(d, u.time, c, OCTAVES);
+        if alpha <= 0.0 { return Vec4::ZERO; }
+        // Normal radial (la de la malla tiene el relieve del planeta)
+        let n = object_pos(vary.pos_ws, u);
+        let light = Vec3::from_scalar(0.04) + lambert(vary.pos_ws, n, u);
+        to_color_alpha(Vec3::new(0.95, 0.96, 0.98).hadamard(light), alpha)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_controls_cloud_fraction() {
+        let cloudy = |coverage: f32| {
+            let c = CloudParams { coverage, ..CloudParams::default() };
+            let mut n = 0;
+            for i in 0..40 {
+                for j in 0..20 {
+                    let (lon, lat) = (i as f32 / 40.0 * std::f32::consts::TAU, (j as f32 / 19.0 - 0.5) * 3.0);
+                    let d = Vec3::new(lat.cos() * lon.cos(), lat.sin(), lat.cos() * lon.sin());
+                    if Clouds::opacity(d, 7.0, &c, OCTAVES) > 0.5 * c.density { n += 1; }
+                }
+            }
+            n as f32 / 800.0
+        };
+        let (none, half, most) = (cloudy(0.0), cloudy(0.5), cloudy(0.9));
+        assert_eq!(none, 0.0);
+        assert!(half > 0.2 && half < 0.8, "{}", half);
+        assert!(most > half, "{} {}", most, half);
+    }
+}
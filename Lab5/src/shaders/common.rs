@@ -1,5 +1,6 @@
-use crate::math::Vec3;
-use crate::renderer::buffers::Color;
+use crate::math::{Vec2, Vec3, Vec4};
+use crate::renderer::{light::Light, raster::Varyings, tonemap::{srgb_to_linear, linear_to_srgb}, uniforms::Uniforms};
+use crate::renderer::texture::{Filter, Sampler, Texture2D, Wrap};
 use super::noise::{noise_3d, NoiseType};
 
 // Helpers comunes para shaders
@@ -10,6 +11,13 @@ pub fn saturate(x: f32) -> f32 { x.clamp(0.0, 1.0) }
 #[inline]
 pub fn lerp(a: f32, b: f32, t: f32) -> f32 { a + (b - a)*t }
 
+/// Hermite entre `edge0` y `edge1` (0 antes, 1 después); con `edge0 > edge1` baja
+#[inline]
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = saturate((x - edge0) / (edge1 - edge0));
+    t * t * (3.0 - 2.0 * t)
+}
+
 #[inline]
 pub fn lerp3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
     Vec3::new(
@@ -19,23 +27,73 @@ pub fn lerp3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
     )
 }
 
+/// Color ajustado "a ojo" en pantalla (sRGB) -> radiancia lineal para el framebuffer HDR.
+/// Valores > 1 se conservan (emisión) y los comprime el tonemapping.
 #[inline]
-pub fn to_color(v: Vec3) -> Color {
-    Color::from_f32_rgb(v.x, v.y, v.z)
+pub fn to_color(v: Vec3) -> Vec4 {
+    to_color_alpha(v, 1.0)
 }
 
+/// Color con alpha (para shaders que se dibujan con `BlendMode`)
 #[inline]
-pub fn lambert(n: Vec3, l: Vec3) -> f32 { n.normalize().dot(l.normalize()).max(0.0) }
+pub fn to_color_alpha(v: Vec3, a: f32) -> Vec4 {
+    Vec4::new(srgb_to_linear(v.x), srgb_to_linear(v.y), srgb_to_linear(v.z), a)
+}
 
+/// `Light::incident` con el color (lineal) pasado al espacio sRGB de las paletas, para
+/// multiplicarlo por albedos de ese espacio antes de `to_color`
 #[inline]
-pub fn rim(n: Vec3, view: Vec3, power: f32) -> f32 { (1.0 - n.normalize().dot(view.normalize()).max(0.0)).powf(power) }
+pub fn incident_srgb(light: &Light, p: Vec3) -> (Vec3, Vec3) {
+    Light { color: linear_to_srgb3(light.color), ..*light }.incident(p)
+}
+
+/// Difuso de Lambert sumado sobre todas las luces de `u` en el punto `p` (mundo) con
+/// normal `n`: color·intensidad·atenuación·max(n·l, 0). La luz principal pasa por el
+/// mapa de sombras. Con la luz por defecto (blanca, intensidad 1) es el `n·l` clásico.
+#[inline]
+pub fn lambert(p: Vec3, n: Vec3, u: &Uniforms) -> Vec3 {
+    let n = n.normalize();
+    let mut sum = Vec3::ZERO;
+    for (i, light) in u.lights.iter().enumerate() {
+        let (l, radiance) = incident_srgb(light, p);
+        let ndl = n.dot(l);
+        if ndl <= 0.0 { continue; }
+        let vis = if i == 0 { shadow(p, n, u) } else { 1.0 };
+        sum += radiance * (ndl * vis);
+    }
+    sum
+}
+
+/// Especular de Blinn-Phong sumado sobre todas las luces (`view` apunta a la cámara)
+#[inline]
+pub fn specular(p: Vec3, n: Vec3, view: Vec3, power: f32, u: &Uniforms) -> Vec3 {
+    let n = n.normalize();
+    let mut sum = Vec3::ZERO;
+    for (i, light) in u.lights.iter().enumerate() {
+        let (l, radiance) = incident_srgb(light, p);
+        if n.dot(l) <= 0.0 { continue; }
+        let h = (l + view).normalize();
+        let vis = if i == 0 { shadow(p, n, u) } else { 1.0 };
+        sum += radiance * (n.dot(h).max(0.0).powf(power) * vis);
+    }
+    sum
+}
 
+/// Fracción de la luz principal que llega a `p` según el mapa de sombras (1 sin mapa).
+/// La normal se orienta hacia la luz para que las superficies de dos caras, como los
+/// anillos, corran el punto hacia el lado iluminado.
 #[inline]
-pub fn specular(n: Vec3, l: Vec3, view: Vec3, power: f32) -> f32 {
-    let h = (l + view).normalize();
-    n.dot(h).max(0.0).powf(power)
+pub fn shadow(p: Vec3, n: Vec3, u: &Uniforms) -> f32 {
+    let (Some(map), Some(main)) = (u.shadow, u.lights.main()) else { return 1.0 };
+    let (l, _) = main.incident(p);
+    let n = n.normalize();
+    let n = if n.dot(l) < 0.0 { -n } else { n };
+    map.visibility(p, n)
 }
 
+#[inline]
+pub fn rim(n: Vec3, view: Vec3, power: f32) -> f32 { (1.0 - n.normalize().dot(view.normalize()).max(0.0)).powf(power) }
+
 /// FBM con tipo explícito
 pub fn fbm_3d_type(p: Vec3, oct: i32, lac: f32, gain: f32, scale: f32, noise_type: NoiseType) -> f32 {
     let mut freq = scale;
@@ -56,6 +114,96 @@ pub fn fbm_3d(p: Vec3, oct: i32, lac: f32, gain: f32, scale: f32) -> f32 {
     fbm_3d_type(p, oct, lac, gain, scale, NoiseType::Perlin)
 }
 
+/// Cuánto conservar de una onda `sin(freq * x)` si un pixel cubre `width` unidades de x:
+/// 1 hasta 1/4 de ciclo por pixel, 0 desde Nyquist (1/2 ciclo por pixel)
+#[inline]
+pub fn band_limit(freq: f32, width: f32) -> f32 {
+    1.0 - smoothstep(0.25, 0.5, freq * width / std::f32::consts::TAU)
+}
+
+/// FBM que apaga las octavas más finas que el pixel (`width` = `Varyings::footprint`
+/// medido en las unidades de `p`). Las octavas apagadas aportan su media (0).
+pub fn fbm_3d_filtered(p: Vec3, oct: i32, lac: f32, gain: f32, scale: f32, width: f32, noise_type: NoiseType) -> f32 {
+    let mut freq = scale;
+    let mut amp = 1.0;
+    let mut sum = 0.0;
+    let mut norm = 0.0;
+    for _ in 0..oct {
+        let keep = band_limit(freq * std::f32::consts::TAU, width);
+        if keep > 0.0 {
+            sum += noise_3d(p * freq, noise_type) * amp * keep;
+        }
+        norm += amp;
+        freq *= lac;
+        amp *= gain;
+    }
+    sum / norm.max(1e-6)
+}
+
+/// Sampler de mapas de superficie: u da la vuelta al ecuador, v se recorta en los polos
+pub const SURFACE_SAMPLER: Sampler = Sampler { filter: Filter::Trilinear, wrap_u: Wrap::Repeat, wrap_v: Wrap::Clamp };
+
+/// Muestrea un mapa de color y lo devuelve en sRGB, el mismo espacio en que
+/// están las paletas de los shaders (`to_color` lo vuelve a pasar a lineal)
+#[inline]
+pub fn sample_srgb(tex: &Texture2D, uv: Vec2, duv_dx: Vec2, duv_dy: Vec2) -> Vec3 {
+    linear_to_srgb3(tex.sample_grad(&SURFACE_SAMPLER, uv, duv_dx, duv_dy).xyz())
+}
+
+/// RGB lineal -> el espacio (sRGB) en el que trabajan los shaders antes de `to_color`
+#[inline]
+pub fn linear_to_srgb3(c: Vec3) -> Vec3 {
+    Vec3::new(linear_to_srgb(c.x), linear_to_srgb(c.y), linear_to_srgb(c.z))
+}
+
+/// Coordenadas equirectangulares (u = longitud, v = latitud, v = 1 en el polo norte)
+/// del punto `pos_ws` en el espacio propio del cuerpo, deshaciendo `u.model` y el giro
+/// `spin` (radianes en y) que el shader aplicó en el vertex. Se calcula por fragmento,
+/// así que no hay costura aunque la malla no traiga uvs útiles.
+pub fn sphere_uv(pos_ws: Vec3, u: &Uniforms, spin: f32) -> Vec2 {
+    let d = body_dir(pos_ws, u, spin);
+    Vec2::new(d.z.atan2(d.x) / std::f32::consts::TAU + 0.5, d.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI + 0.5)
+}
+
+/// Dirección (unitaria) de `pos_ws` desde el centro del cuerpo, en su espacio propio:
+/// deshace `u.model` y el giro `spin`, así lo que se calcule con ella gira con la superficie
+pub fn body_dir(pos_ws: Vec3, u: &Uniforms, spin: f32) -> Vec3 {
+    let p = object_pos(pos_ws, u);
+    let m = &u.model.m;
+    // Transpuesta de la parte 3x3 (rotación * escala uniforme); la escala se va al normalizar
+    let q = Vec3::new(
+        m[0][0]*p.x + m[1][0]*p.y + m[2][0]*p.z,
+        m[0][1]*p.x + m[1][1]*p.y + m[2][1]*p.z,
+        m[0][2]*p.x + m[1][2]*p.y + m[2][2]*p.z,
+    );
+    let (s, c) = spin.sin_cos();
+    Vec3::new(c*q.x - s*q.z, q.y, s*q.x + c*q.z).normalize()
+}
+
+/// `sphere_uv` del fragmento junto con sus derivadas en pantalla (a partir de las de
+/// `pos_ws`). El salto de u en la costura se envuelve para no elegir un mip borroso.
+pub fn sphere_uv_grad(vary: &Varyings, u: &Uniforms, spin: f32) -> (Vec2, Vec2, Vec2) {
+    let uv = sphere_uv(vary.pos_ws, u, spin);
+    let d = |dp: Vec3| {
+        let o = sphere_uv(vary.pos_ws + dp, u, spin) - uv;
+        Vec2::new(o.x - o.x.round(), o.y)
+    };
+    (uv, d(vary.dfdx.pos_ws), d(vary.dfdy.pos_ws))
+}
+
+/// Posición relativa al centro del cuerpo (origen de `u.model`): el ruido queda
+/// pegado a la superficie aunque el cuerpo orbite por la escena
+#[inline]
+pub fn object_pos(pos_ws: Vec3, u: &Uniforms) -> Vec3 {
+    pos_ws - Vec3::new(u.model.m[0][3], u.model.m[1][3], u.model.m[2][3])
+}
+
+/// Escala uniforme de `u.model` (largo de su primera columna)
+#[inline]
+pub fn model_scale(u: &Uniforms) -> f32 {
+    Vec3::new(u.model.m[0][0], u.model.m[1][0], u.model.m[2][0]).length()
+}
+
 /// Gradiente por latitud usando la normal Y en espacio mundo (reinstaurado)
 #[inline]
 pub fn latitude(v: Vec3) -> f32 { (v.y * 0.5) + 0.5 }
\ No newline at end of file
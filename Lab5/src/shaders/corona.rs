@@ -0,0 +1,127 @@
+//! Corona y protuberancias: un volumen alrededor de la estrella, integrado por ray marching.
+//!
+//! Se dibuja sobre una esfera `SHELL` veces más grande que la estrella (hijo `Corona` del
+//! nodo estrella), mostrando sus caras traseras: así cada pixel cubre todo el tramo del rayo
+//! dentro de la cáscara y el test de profundidad contra la superficie ya dibujada deja la
+//! corona fuera de la silueta. Se suma (`BlendMode::Additive`) después de la superficie.
+
+use crate::math::{Vec3, Vec4};
+use crate::renderer::{uniforms::{StarParams, Uniforms}, pipeline::{Shader, VertexIn, VertexOut}};
+use super::common::*;
+use super::blackbody::blackbody;
+use super::noise::NoiseType;
+
+/// Radio de la cáscara, en radios de la estrella
+pub const SHELL: f32 = 1.9;
+
+// Pasos del ray marching por pixel
+const STEPS: usize = 20;
+
+// Alturas de escala (en radios) de la corona y de las protuberancias
+const CORONA_H: f32 = 0.2;
+const PROMINENCE_H: f32 = 0.12;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Corona;
+
+impl Corona {
+    /// Densidad de la corona y de las protuberancias en `d`, un punto en el espacio propio
+    /// de la estrella medido en radios (|d| >= 1). La corona cae exponencialmente con la
+    /// altura y se abre en serpentinas radiales; las protuberancias son arcos finos (las
+    /// crestas de un ruido 3D) cerca de la superficie, sólo sobre algunas regiones activas.
+    /// Ambas escalan con `flare_intensity` (1 con el valor por defecto).
+    pub fn density(d: Vec3, t: f32, params: &StarParams) -> (f32, f32) {
+        let r = d.length().max(1e-6);
+        let h = (r - 1.0).max(0.0);
+        let dir = d / r;
+        let gain = params.flare_intensity / 0.45;
+
+        // Serpentinas: el ruido depende sólo de la dirección, así se estira hacia afuera
+        // (el fbm de valor cae casi siempre en [0.6, 0.9], con media ~0.75)
+        let n = fbm_3d_type(dir * 2.0 + Vec3::new(0.0, t * 0.02, 0.0), 3, 2.0, 0.5, 1.0, NoiseType::Perlin);
+        let streamers = 0.15 + 1.6 * smoothstep(0.74, 0.86, n);
+        // Se apaga antes del borde de la cáscara para que no se note el corte
+        let edge = 1.0 - smoothstep(0.5, 1.0, h / (SHELL - 1.0));
+        let corona = (-h / CORONA_H).exp() * streamers * edge;
+
+        let active = smoothstep(0.78, 0.86, fbm_3d_type(dir * 1.5 + Vec3::new(t * 0.01, 3.7, 0.0), 2, 2.0, 0.5, 1.0, NoiseType::Perlin));
+        let loops = fbm_3d_type(d * 2.5 + Vec3::new(t * 0.05, -t * 0.04, t * 0.03), 2, 2.0, 0.5, 1.0, NoiseType::Perlin);
+        let ridge = 1.0 - ((loops - 0.75).abs() / 0.1).min(1.0);
+        let prominence = smoothstep(0.7, 0.95, ridge) * (-h / PROMINENCE_H).exp() * active;
+
+        (corona * gain, prominence * gain)
+    }
+}
+
+impl Shader for Corona {
+    fn name(&self) -> &'static str { "Corona" }
+
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+        let clip = u.proj * u.view * u.model * Vec4::from3(vin.pos, 1.0);
+        let pos_ws = (u.model * Vec4::from3(vin.pos, 1.0)).xyz();
+        let nrm_ws = (u.model * Vec4::from3(vin.nrm, 0.0)).xyz();
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
+    }
+
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
+        let t = u.time;
+        let params = &u.star;
+        // El fragmento está sobre la cáscara: su distancia al centro es el radio (así no
+        // depende del tamaño de la malla)
+        let shell = object_pos(vary.pos_ws, u).length().max(1e-6);
+        let radius = shell / SHELL;
+        let spin = t * params.rot_speed;
+
+        // Tramo del rayo de la cámara dentro de la cáscara, cortado por la estrella
+        let eye = u.camera_pos;
+        let dir = (vary.pos_ws - eye).normalize();
+        let oc = object_pos(eye, u);
+        let b = oc.dot(dir);
+        let c2 = oc.dot(oc);
+        let disc = b * b - (c2 - shell * shell);
+        if disc <= 0.0 { return Vec4::new(0.0, 0.0, 0.0, 1.0); }
+        let t0 = (-b - disc.sqrt()).max(0.0);
+        let mut t1 = -b + disc.sqrt();
+        let disc_star = b * b - (c2 - radius * radius);
+        if disc_star > 0.0 && -b - disc_star.sqrt() > 0.0 {
+            t1 = t1.min(-b - disc_star.sqrt());
+        }
+        if t1 <= t0 { return Vec4::new(0.0, 0.0, 0.0, 1.0); }
+
+        // Desfase por pixel del primer paso: cambia las bandas del muestreo por ruido fino
+        let dt = (t1 - t0) / STEPS as f32;
+        let jitter = (vary.pos_ws.dot(Vec3::new(12.9898, 78.233, 37.719)).sin() * 43758.547).fract().abs();
+        let (mut corona, mut prominence) = (0.0f32, 0.0f32);
+        for i in 0..STEPS {
+            let p = eye + dir * (t0 + (i as f32 + jitter) * dt);
+            let r = object_pos(p, u).length() / radius;
+            let (c, pr) = Corona::density(body_dir(p, u, spin) * r, t, params);
+            corona += c;
+            prominence += pr;
+        }
+        // Integral en radios de la estrella
+        let ds = dt / radius;
+        let white = blackbody(params.temperature);
+        let red = Vec3::new(1.0, 0.35, 0.25);
+        // Ya en RGB lineal: se suma directo al framebuffer HDR
+        let col = white * (corona * ds * 0.3) + red * (prominence * ds * 3.0);
+        Vec4::from3(col, 1.0)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corona_fades_with_height_and_flare_intensity() {
+        let params = StarParams::default();
+        let dir = Vec3::new(0.6, 0.3, -0.74).normalize();
+        let at = |h: f32, p: &StarParams| Corona::density(dir * (1.0 + h), 2.0, p).0;
+        assert!(at(0.05, &params) > at(0.4, &params));
+        assert!(at(0.4, &params) > at(0.85, &params));
+        let calm = StarParams { flare_intensity: 0.0, ..params };
+        assert_eq!(Corona::density(dir * 1.05, 2.0, &calm), (0.0, 0.0));
+    }
+}
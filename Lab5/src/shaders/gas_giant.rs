@@ -1,6 +1,7 @@
-use crate::math::{Vec3, Vec4, rotation_y};
-use crate::renderer::{buffers::Color, uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::math::{Vec2, Vec3, Vec4, rotation_y};
+use crate::renderer::{uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
 use super::common::*;
+use super::clouds::Clouds;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Gas {
@@ -23,7 +24,8 @@ impl Gas {
         latitude(n_ws) // [0,1]
     }
 
-    fn color_layers(&self, p_ws: Vec3, n_ws: Vec3, view_dir: Vec3, u: &Uniforms) -> Vec3 {
+    /// `diffuse`: Lambert de todas las luces (`lambert`), con la sombra de las nubes
+    fn color_layers(&self, p_ws: Vec3, n_ws: Vec3, (uv, duv_dx, duv_dy): (Vec2, Vec2, Vec2), view_dir: Vec3, diffuse: Vec3, u: &Uniforms) -> Vec3 {
         // Bandas por latitud + turbulencia
         let lat = Self::lat_from_normal(n_ws); // 0 en sur, 1 en norte
         let phi = lat*std::f32::consts::TAU*self.band_freq;
@@ -31,7 +33,14 @@ impl Gas {
         let turb = fbm_3d(p_ws + Vec3::new(3.2,7.7,1.5), 4, 2.0, 0.5, u.planet.noise_scale*1.4);
         let s = (phi + turb*3.5).sin()*0.5 + 0.5; // 0..1 ondulado
 
-        let bands = lerp3(self.main_a, self.main_b, s);
+        let mut bands = match u.textures.albedo {
+            Some(tex) => sample_srgb(tex, uv, duv_dx, duv_dy),
+            None => lerp3(self.main_a, self.main_b, s),
+        };
+        // Mapa de nubes: máscara en el canal rojo
+        if let Some(tex) = u.textures.clouds {
+            bands = lerp3(bands, Vec3::new(0.95, 0.93, 0.9), tex.sample_grad(&SURFACE_SAMPLER, uv, duv_dx, duv_dy).x);
+        }
 
         // Mancha  que rota con el planeta
         let _spot_phase = (u.time*0.4).sin()*0.5 + 0.5;
@@ -40,30 +49,34 @@ impl Gas {
         let spot = (dot_spot.powf(50.0)) * 0.6; 
         let bands_spot = bands * (1.0 - spot) + Vec3::new(0.7,0.35,0.2)*spot;
 
-        // Suave rim
-        let rim_k = rim(n_ws, view_dir, 2.2) * 0.35;
+        // Terminador suave: la atmósfera deja algo de luz en el lado oscuro
+        let diff = Vec3::from_scalar(0.3) + diffuse * 0.7;
+
+        // Suave rim (la capa de atmósfera, si hay, lo reemplaza)
+        let rim_k = if u.planet.has_atmosphere { 0.0 } else { rim(n_ws, view_dir, 2.2) * 0.35 };
 
-        (bands_spot + Vec3::new(0.3,0.35,0.4)*rim_k).clamp01()
+        (bands_spot.hadamard(diff) + Vec3::new(0.3,0.35,0.4)*rim_k).clamp01()
     }
 }
 
 impl Shader for Gas {
     fn name(&self) -> &'static str { "GasGiant" }
 
-    fn vertex(&mut self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
         let rot = rotation_y(u.time * u.planet.rotation_speed * 0.7);
-        let model = rot * u.model;
+        let model = u.model * rot;
 
         let clip = u.proj * u.view * model * Vec4::from3(vin.pos, 1.0);
         let pos_ws = (model * Vec4::from3(vin.pos, 1.0)).xyz();
         let nrm_ws = (model * Vec4::from3(vin.nrm, 0.0)).xyz().normalize();
 
-        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv }
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
     }
 
-    fn fragment(&mut self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Color {
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
         let view_dir = (u.camera_pos - vary.pos_ws).normalize();
-        let c = self.color_layers(vary.pos_ws, vary.nrm_ws, view_dir, u);
+        let uv = sphere_uv_grad(vary, u, u.time * u.planet.rotation_speed * 0.7);
+        let c = self.color_layers(object_pos(vary.pos_ws, u), vary.nrm_ws, uv, view_dir, lambert(vary.pos_ws, vary.nrm_ws, u) * Clouds::shadow(vary.pos_ws, u), u);
         to_color(c)
     }
 }
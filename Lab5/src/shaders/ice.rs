@@ -1,6 +1,7 @@
 use crate::math::{Vec3, Vec4, rotation_y};
-use crate::renderer::{buffers::Color, uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::renderer::{uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
 use super::common::*;
+use super::clouds::Clouds;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Ice {
@@ -14,7 +15,8 @@ impl Default for Ice {
 }
 
 impl Ice {
-    fn color_layers(&self, p_ws: Vec3, n_ws: Vec3, view_dir: Vec3, u: &Uniforms) -> Vec3 {
+    /// `diffuse`: Lambert de todas las luces (`lambert`), con la sombra de las nubes
+    fn color_layers(&self, p_ws: Vec3, n_ws: Vec3, view_dir: Vec3, diffuse: Vec3, u: &Uniforms) -> Vec3 {
         // base azul 
         let base = Vec3::new(0.05, 0.12, 0.18);
 
@@ -25,11 +27,10 @@ impl Ice {
         // capas de hielo y escarcha
         let frost_layer = lerp3(base, self.frost, crack*0.9);
 
-        // brillo simulado como rim+lambert
-        let diff = lambert(n_ws, u.light_dir);
-        let rim_k = rim(n_ws, view_dir, u.planet.rim_power*1.2)*0.6;
+        // brillo simulado como rim+lambert (la capa de atmósfera, si hay, lo reemplaza)
+        let rim_k = if u.planet.has_atmosphere { 0.0 } else { rim(n_ws, view_dir, u.planet.rim_power*1.2)*0.6 };
 
-        let col = frost_layer * (0.4 + 0.6*diff) + Vec3::new(0.9,0.95,1.0)*rim_k*0.5;
+        let col = frost_layer.hadamard(Vec3::from_scalar(0.4) + diffuse*0.6) + Vec3::new(0.9,0.95,1.0)*rim_k*0.5;
         (col * (1.0 - cracks) + Vec3::new(0.08,0.06,0.05)*cracks).clamp01()
     }
 }
@@ -37,22 +38,22 @@ impl Ice {
 impl Shader for Ice {
     fn name(&self) -> &'static str { "IcePlanet" }
 
-    fn vertex(&mut self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
 
-    let orbit_rot = rotation_y(u.time * u.planet.rotation_speed * 0.6);
+    let spin = rotation_y(u.time * u.planet.rotation_speed * 0.6);
     let self_rot = rotation_y(u.time * self.rot_speed);
-    let model = orbit_rot * u.model * self_rot;
+    let model = u.model * spin * self_rot;
 
         let clip = u.proj * u.view * model * Vec4::from3(vin.pos, 1.0);
         let pos_ws = (model * Vec4::from3(vin.pos, 1.0)).xyz();
         let nrm_ws = (model * Vec4::from3(vin.nrm, 0.0)).xyz().normalize();
 
-        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv }
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
     }
 
-    fn fragment(&mut self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Color {
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
         let view_dir = (u.camera_pos - vary.pos_ws).normalize();
-        let c = self.color_layers(vary.pos_ws, vary.nrm_ws, view_dir, u);
+        let c = self.color_layers(object_pos(vary.pos_ws, u), vary.nrm_ws, view_dir, lambert(vary.pos_ws, vary.nrm_ws, u) * Clouds::shadow(vary.pos_ws, u), u);
         to_color(c)
     }
 }
@@ -1,16 +1,23 @@
+//! Shaders procedurales (estrella y su corona, planetas con atmósfera y nubes, mundo oceánico, anillos, luna) y fábrica [`make_shader`].
+
 pub mod common;
 pub mod noise; // ruido extra (Perlin/Simplex/Cellular)
+pub mod blackbody;
 pub mod flat;
 pub mod star;
+pub mod corona;
+pub mod atmosphere;
+pub mod clouds;
 pub mod rocky_planet;
 pub mod gas_giant;
 pub mod scifi_planet;
 pub mod lava;
 pub mod ice;
+pub mod ocean;
 pub mod rings_vs; // nombres originales
 pub mod moon_vs;
 
-use crate::renderer::pipeline::Shader;
+use crate::renderer::{pipeline::Shader, RenderState, BlendMode, CullMode};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ShaderKind {
@@ -20,21 +27,115 @@ pub enum ShaderKind {
     SciFi,
     Lava,
     Ice,
+    Ocean,
     Star,
     Rings,
     Moon,
+    Corona,
+    Atmosphere,
+    Clouds,
+}
+
+impl ShaderKind {
+    pub const ALL: [ShaderKind; 13] = [
+        ShaderKind::Flat, ShaderKind::Rocky, ShaderKind::Gas, ShaderKind::SciFi, ShaderKind::Lava,
+        ShaderKind::Ice, ShaderKind::Ocean, ShaderKind::Star, ShaderKind::Rings, ShaderKind::Moon, ShaderKind::Corona,
+        ShaderKind::Atmosphere, ShaderKind::Clouds,
+    ];
+
+    /// Cuerpos asignables en runtime (acciones `Shader1`..`Shader7`)
+    pub const BODIES: [ShaderKind; 7] = [
+        ShaderKind::Rocky, ShaderKind::Gas, ShaderKind::SciFi, ShaderKind::Lava, ShaderKind::Ice, ShaderKind::Star,
+        ShaderKind::Ocean,
+    ];
+
+    /// Nombre corto usado en la línea de comandos
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderKind::Flat  => "flat",
+            ShaderKind::Rocky => "rocky",
+            ShaderKind::Gas   => "gas",
+            ShaderKind::SciFi => "scifi",
+            ShaderKind::Lava  => "lava",
+            ShaderKind::Ice   => "ice",
+            ShaderKind::Ocean => "ocean",
+            ShaderKind::Star  => "star",
+            ShaderKind::Rings => "rings",
+            ShaderKind::Moon  => "moon",
+            ShaderKind::Corona => "corona",
+            ShaderKind::Atmosphere => "atmosphere",
+            ShaderKind::Clouds => "clouds",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        Self::ALL.iter().copied().find(|k| k.name() == s)
+    }
+
+    /// Estado de pipeline recomendado: los anillos (disco aplanado) se ven por ambas caras
+    /// y se mezclan con alpha sobre lo que tienen detrás, y las nubes igual pero sólo de
+    /// frente; la corona se suma sobre la estrella mostrando sólo sus caras traseras (ver
+    /// `corona`), y la atmósfera cubre al planeta con sus caras delanteras (ver `atmosphere`)
+    pub fn render_state(self) -> RenderState {
+        match self {
+            ShaderKind::Rings => RenderState::transparent(BlendMode::Alpha),
+            ShaderKind::Corona => RenderState { cull: CullMode::Front, ..RenderState::transparent(BlendMode::Additive) },
+            ShaderKind::Clouds => RenderState { cull: CullMode::Back, ..RenderState::transparent(BlendMode::Alpha) },
+            ShaderKind::Atmosphere => RenderState { cull: CullMode::Back, ..RenderState::transparent(BlendMode::Premultiplied) },
+            _ => RenderState::opaque(),
+        }
+    }
+}
+
+/// Una instancia de cada shader, compartida por todos los nodos que la usan
+pub struct ShaderLibrary {
+    shaders: Vec<Box<dyn Shader>>,
+}
+
+impl Default for ShaderLibrary {
+    fn default() -> Self {
+        Self { shaders: ShaderKind::ALL.iter().map(|&k| make_shader(k)).collect() }
+    }
+}
+
+impl ShaderLibrary {
+    pub fn new() -> Self { Self::default() }
+
+    /// Indexa por `kind as usize`: `ShaderKind::ALL` tiene que seguir el orden de la declaración
+    pub fn get(&self, kind: ShaderKind) -> &dyn Shader {
+        self.shaders[kind as usize].as_ref()
+    }
 }
 
 pub fn make_shader(kind: ShaderKind) -> Box<dyn Shader> {
     match kind {
-        ShaderKind::Flat  => Box::new(flat::Flat::default()),
+        ShaderKind::Flat  => Box::new(flat::Flat),
         ShaderKind::Rocky => Box::new(rocky_planet::Rocky::default()),
         ShaderKind::Gas   => Box::new(gas_giant::Gas::default()),
         ShaderKind::SciFi => Box::new(scifi_planet::SciFi::default()),
         ShaderKind::Lava  => Box::new(lava::Lava::default()),
         ShaderKind::Ice   => Box::new(ice::Ice::default()),
-        ShaderKind::Star  => Box::new(star::Star::default()),
+        ShaderKind::Ocean => Box::new(ocean::Ocean::default()),
+        ShaderKind::Star  => Box::new(star::Star),
         ShaderKind::Rings => Box::new(rings_vs::Rings::default()),
-        ShaderKind::Moon  => Box::new(moon_vs::Moon::default()),
+        ShaderKind::Moon  => Box::new(moon_vs::Moon),
+        ShaderKind::Corona => Box::new(corona::Corona),
+        ShaderKind::Atmosphere => Box::new(atmosphere::Atmosphere),
+        ShaderKind::Clouds => Box::new(clouds::Clouds),
     }
-}
\ No newline at end of file
+}
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_follows_declaration_order() {
+        let library = ShaderLibrary::new();
+        for (i, &kind) in ShaderKind::ALL.iter().enumerate() {
+            assert_eq!(kind as usize, i, "{:?}", kind);
+            assert_eq!(library.get(kind).name(), make_shader(kind).name());
+        }
+    }
+}
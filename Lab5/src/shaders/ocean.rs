@@ -0,0 +1,191 @@
+//! Planeta oceánico tipo Tierra: continentes donde un ruido de altura supera el nivel del
+//! mar, biomas por latitud, altura y humedad, casquetes polares, reflejo del sol sobre el
+//! agua (GGX + Fresnel) y luces de ciudades que se encienden del lado de noche.
+//!
+//! La superficie se calcula por fragmento con `body_dir`, así gira sin mover la malla y no
+//! tiene costura; la normal es la radial (el relieve de la malla no coincide con los continentes).
+
+use crate::math::{Vec3, Vec4};
+use crate::renderer::{uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use super::common::*;
+use super::clouds::Clouds;
+use super::noise::NoiseType;
+use std::f32::consts::PI;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Ocean {
+    pub rot_speed: f32,
+    pub sea_level: f32,      // altura (0..1) bajo la que hay agua
+    pub continent_scale: f32,
+    pub roughness: f32,      // rugosidad GGX del agua
+    pub polar_cap: f32,      // |latitud| (seno) desde la que hay hielo
+    pub city_intensity: f32,
+    pub deep:    Vec3,
+    pub shallow: Vec3,
+    pub sand:    Vec3,
+    pub grass:   Vec3,
+    pub forest:  Vec3,
+    pub desert:  Vec3,
+    pub tundra:  Vec3,
+    pub rock:    Vec3,
+    pub snow:    Vec3,
+    pub city:    Vec3,
+}
+
+impl Default for Ocean {
+    fn default() -> Self {
+        Self {
+            rot_speed: 0.05,
+            sea_level: 0.62,
+            continent_scale: 1.6,
+            roughness: 0.18,
+            polar_cap: 0.86,
+            city_intensity: 1.6,
+            deep:    Vec3::new(0.02, 0.07, 0.20),
+            shallow: Vec3::new(0.07, 0.32, 0.42),
+            sand:    Vec3::new(0.80, 0.74, 0.55),
+            grass:   Vec3::new(0.33, 0.50, 0.20),
+            forest:  Vec3::new(0.12, 0.32, 0.12),
+            desert:  Vec3::new(0.82, 0.68, 0.45),
+            tundra:  Vec3::new(0.46, 0.46, 0.38),
+            rock:    Vec3::new(0.42, 0.37, 0.32),
+            snow:    Vec3::new(0.93, 0.95, 0.98),
+            city:    Vec3::new(1.00, 0.78, 0.45),
+        }
+    }
+}
+
+/// Lo que sale del mapa de la superficie en una dirección
+struct Surface {
+    albedo: Vec3,
+    water: f32,  // 1 en el mar abierto, 0 en tierra o hielo
+    cities: f32, // densidad de luces (0..1)
+}
+
+impl Ocean {
+    /// Altura del terreno en `d` (dirección en el espacio propio), en [0, 1]
+    fn height(&self, d: Vec3) -> f32 {
+        // El fbm simplex cae casi siempre en [0.3, 0.7]: se lleva a [0, 1]
+        let h = fbm_3d_type(d * self.continent_scale + Vec3::new(5.3, 1.1, 8.7), 6, 2.0, 0.5, 1.0, NoiseType::Simplex);
+        saturate((h - 0.3) / 0.4)
+    }
+
+    fn surface(&self, d: Vec3) -> Surface {
+        let h = self.height(d);
+        let lat = d.y.abs();
+        // Borde de los casquetes irregular
+        let wobble = fbm_3d_type(d * 3.0 + Vec3::new(2.0, 0.0, 4.0), 3, 2.0, 0.5, 1.0, NoiseType::Simplex) - 0.5;
+        let ice = smoothstep(-0.02, 0.02, lat + wobble * 0.15 - self.polar_cap);
+
+        if h < self.sea_level {
+            // Agua: más clara cerca de la costa
+            let depth = saturate((self.sea_level - h) / 0.18);
+            let albedo = lerp3(self.shallow, self.deep, depth.sqrt());
+            return Surface { albedo: lerp3(albedo, self.snow, ice), water: 1.0 - ice, cities: 0.0 };
+        }
+
+        // Tierra: altura sobre el mar (0 en la costa, 1 en las cumbres), temperatura por
+        // latitud y altura, humedad de otro ruido
+        let e = saturate((h - self.sea_level) / (1.0 - self.sea_level));
+        let moist = saturate((fbm_3d_type(d * 2.5 + Vec3::new(9.1, 4.2, 0.7), 4, 2.0, 0.5, 1.0, NoiseType::Simplex) - 0.3) / 0.4);
+        let temp = 1.0 - lat * 1.15 - e * 0.5;
+
+        let warm = lerp3(self.desert, self.forest, smoothstep(0.35, 0.6, moist));
+        let mild = lerp3(self.grass, self.forest, smoothstep(0.45, 0.7, moist));
+        let mut albedo = lerp3(mild, warm, smoothstep(0.55, 0.75, temp));
+        albedo = lerp3(self.tundra, albedo, smoothstep(0.15, 0.35, temp));
+        albedo = lerp3(self.sand, albedo, smoothstep(0.0, 0.04, e));
+        albedo = lerp3(albedo, self.rock, smoothstep(0.55, 0.8, e));
+        let snow = smoothstep(0.1, 0.0, temp).max(ice);
+        albedo = lerp3(albedo, self.snow, snow);
+
+        // Ciudades: manchas de un ruido fino, más en tierras bajas y templadas
+        let spots = fbm_3d_type(d * 24.0 + Vec3::new(1.3, 7.1, 3.9), 3, 2.0, 0.5, 1.0, NoiseType::Simplex);
+        let habitable = smoothstep(0.5, 0.15, e) * smoothstep(0.2, 0.45, temp) * (1.0 - snow);
+        let cities = smoothstep(0.55, 0.68, spots) * habitable;
+        Surface { albedo, water: 0.0, cities }
+    }
+
+    /// Especular GGX con Fresnel de Schlick sumado sobre todas las luces (`view` apunta a la cámara)
+    fn glint(&self, p: Vec3, n: Vec3, view: Vec3, u: &Uniforms) -> Vec3 {
+        let a2 = (self.roughness * self.roughness).powi(2);
+        let k = (self.roughness + 1.0).powi(2) / 8.0;
+        let ndv = n.dot(view).max(1e-4);
+        let mut sum = Vec3::ZERO;
+        for (i, light) in u.lights.iter().enumerate() {
+            let (l, radiance) = incident_srgb(light, p);
+            let ndl = n.dot(l);
+            if ndl <= 0.0 { continue; }
+            let h = (l + view).normalize();
+            let ndh = n.dot(h).max(0.0);
+            let dist = a2 / (PI * (ndh * ndh * (a2 - 1.0) + 1.0).powi(2));
+            // Agua: F0 = 0.02
+            let fresnel = 0.02 + 0.98 * (1.0 - h.dot(view).max(0.0)).powi(5);
+            let geom = (ndl / (ndl * (1.0 - k) + k)) * (ndv / (ndv * (1.0 - k) + k));
+            let vis = if i == 0 { shadow(p, n, u) } else { 1.0 };
+            sum += radiance * (dist * fresnel * geom / (4.0 * ndv) * vis);
+        }
+        sum
+    }
+}
+
+impl Shader for Ocean {
+    fn name(&self) -> &'static str { "Ocean" }
+
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+        let clip = u.proj * u.view * u.model * Vec4::from3(vin.pos, 1.0);
+        let pos_ws = (u.model * Vec4::from3(vin.pos, 1.0)).xyz();
+        let nrm_ws = (u.model * Vec4::from3(vin.nrm, 0.0)).xyz().normalize();
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
+    }
+
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
+        let spin = u.time * (u.planet.rotation_speed * 0.7 + self.rot_speed);
+        let s = self.surface(body_dir(vary.pos_ws, u, spin));
+        let n = object_pos(vary.pos_ws, u).normalize();
+        let view_dir = (u.camera_pos - vary.pos_ws).normalize();
+
+        let clouds = Clouds::shadow(vary.pos_ws, u);
+        let diffuse = Vec3::from_scalar(0.03) + lambert(vary.pos_ws, n, u) * clouds;
+        let mut col = s.albedo.hadamard(diffuse);
+        col += self.glint(vary.pos_ws, n, view_dir, u) * (s.water * clouds);
+
+        // Luces de ciudades: se encienden a lo largo del terminador de la luz principal
+        if s.cities > 0.0 {
+            let ndl = u.lights.main().map_or(-1.0, |m| n.dot(m.incident(vary.pos_ws).0));
+            let night = 1.0 - smoothstep(-0.15, 0.05, ndl);
+            col += self.city * (s.cities * night * self.city_intensity);
+        }
+        to_color(col)
+    }
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oceans_continents_caps_and_cities() {
+        let ocean = Ocean::default();
+        let (mut water, mut cities) = (0, 0.0);
+        for i in 0..40 {
+            for j in 0..20 {
+                let (lon, lat) = (i as f32 / 40.0 * std::f32::consts::TAU, (j as f32 / 19.0 - 0.5) * 2.4);
+                let s = ocean.surface(Vec3::new(lat.cos() * lon.cos(), lat.sin(), lat.cos() * lon.sin()));
+                if s.water > 0.5 { water += 1; }
+                cities += s.cities;
+            }
+        }
+        // Más mar que tierra, pero hay continentes (y ciudades en ellos)
+        let water = water as f32 / 800.0;
+        assert!(water > 0.5 && water < 0.85, "{}", water);
+        assert!(cities > 0.0);
+        // Los polos son hielo, sin agua abierta ni ciudades
+        for pole in [Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0)] {
+            let s = ocean.surface(pole);
+            assert_eq!((s.water, s.cities), (0.0, 0.0));
+            assert!(s.albedo.x > 0.9);
+        }
+    }
+}
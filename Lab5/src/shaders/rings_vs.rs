@@ -1,7 +1,9 @@
-use crate::math::{Vec3, Vec4, rotation_y};
+use crate::math::{Vec2, Vec3, Vec4, rotation_y};
 use crate::math::mat::rotation_x;
-use crate::renderer::{buffers::Color, uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::renderer::{uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::renderer::texture::{Filter, Sampler, Wrap};
 use super::common::*;
+use super::noise::NoiseType;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Rings {
@@ -19,37 +21,49 @@ impl Default for Rings {
 impl Shader for Rings {
     fn name(&self) -> &'static str { "RingsShader" }
 
-    fn vertex(&mut self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
     // Transformar la esfera en “disco”:
     let mut p = vin.pos;
     p.y *= 0.03;            // aplana
     p.x *= 1.6; p.z *= 1.6; // ensancha
     let tilt_m = rotation_x(self.tilt);
     let rot = rotation_y(u.time * u.planet.rotation_speed * 0.7);
-    let model = rot * tilt_m * u.model;
+    let model = u.model * tilt_m * rot;
 
         let clip = u.proj * u.view * model * Vec4::from3(p, 1.0);
         let pos_ws = (model * Vec4::from3(p, 1.0)).xyz();
         let nrm_ws = Vec3::new(0.0, 1.0, 0.0); 
 
-        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv }
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
     }
 
-    fn fragment(&mut self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Color {
-        let r = (vary.pos_ws.x * vary.pos_ws.x + vary.pos_ws.z * vary.pos_ws.z).sqrt();
-        let bands = (r * 8.0 + fbm_3d(vary.pos_ws * 0.5 + Vec3::new(1.2,0.0,2.3), 3, 2.0, 0.5, 0.8)).sin() * 0.5 + 0.5;
-        let streaks = (r * 120.0 + fbm_3d(vary.pos_ws * 5.0, 2, 2.0, 0.5, 1.4)).sin() * 0.5 + 0.5;
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
+        // Radio medido desde el centro del planeta; `inner`/`outer` están pensados
+        // para un cuerpo de escala 1.2, así que se normaliza a esa escala
+        let k = 1.2 / model_scale(u).max(1e-6);
+        let p = object_pos(vary.pos_ws, u) * k;
+        let radius = |p: Vec3| (p.x * p.x + p.z * p.z).sqrt();
+        let r = radius(p);
+        // Paso radial por pixel: con él se apagan las bandas más finas que el pixel
+        let dr_x = radius(p + vary.dfdx.pos_ws * k) - r;
+        let dr_y = radius(p + vary.dfdy.pos_ws * k) - r;
+        let dr = dr_x.abs().max(dr_y.abs());
+        let width = vary.footprint() * k;
+        let bands = (r * 8.0 + fbm_3d_filtered(p * 0.5 + Vec3::new(1.2,0.0,2.3), 3, 2.0, 0.5, 0.8, width * 0.5, NoiseType::Perlin)).sin()
+            * band_limit(8.0, dr) * 0.5 + 0.5;
+        let streaks = (r * 120.0 + fbm_3d_filtered(p * 5.0, 2, 2.0, 0.5, 1.4, width * 5.0, NoiseType::Perlin)).sin()
+            * band_limit(120.0, dr) * 0.5 + 0.5;
         let col_a = Vec3::new(0.92, 0.88, 0.78);
         let col_b = Vec3::new(0.66, 0.60, 0.48);
         let mut base = lerp3(col_a, col_b, bands);
 
-        base = base * (0.9 + 0.45 * streaks);
+        base *= 0.9 + 0.45 * streaks;
 
         let tx = u.model.m[0][3];
         if tx < -1.5 {
-            base = base + Vec3::new(0.06, 0.08, 0.02);
+            base += Vec3::new(0.06, 0.08, 0.02);
         } else if tx > -1.5 && tx < 0.5 {
-            base = base + Vec3::new(0.04, 0.02, 0.0);
+            base += Vec3::new(0.04, 0.02, 0.0);
         }
 
         let inner = if self.inner > 0.0 { self.inner } else { 0.75 };
@@ -58,9 +72,21 @@ impl Shader for Rings {
         let edge_out = 1.0 - saturate((r - outer) / 0.08);
         let band_alpha = (edge_in * edge_out).clamp(0.0, 1.0);
         let dust = saturate(1.0 - (r - inner) * 0.8);
-        let alpha = band_alpha * (0.25 + 0.75 * dust);
-        let diff = lambert(vary.nrm_ws, u.light_dir) * 0.9 + 0.1;
+        let mut alpha = band_alpha * (0.25 + 0.75 * dust);
 
-        to_color((base * diff * alpha).clamp01())
+        // Mapa de opacidad radial: u = 0 en `inner`, 1 en `outer`
+        if let Some(tex) = u.textures.opacity {
+            let span = outer - inner;
+            let o = tex.sample_grad(
+                &Sampler::new(Filter::Trilinear, Wrap::Clamp),
+                Vec2::new((r - inner) / span, 0.5),
+                Vec2::new(dr_x / span, 0.0),
+                Vec2::new(dr_y / span, 0.0),
+            );
+            alpha = band_alpha * o.x * o.w;
+        }
+        let diff = lambert(vary.pos_ws, vary.nrm_ws, u) * 0.9 + Vec3::from_scalar(0.1);
+
+        to_color_alpha(base.hadamard(diff).clamp01(), alpha)
     }
 }
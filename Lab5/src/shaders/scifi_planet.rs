@@ -1,5 +1,5 @@
-use crate::math::{Vec2, Vec3, Vec4, rotation_y};
-use crate::renderer::{buffers::Color, uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::math::{Vec3, Vec4, rotation_y};
+use crate::renderer::{uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
 use super::common::*;
 
 #[derive(Copy, Clone, Debug)]
@@ -49,28 +49,28 @@ impl SciFi {
 impl Shader for SciFi {
     fn name(&self) -> &'static str { "SciFiPlanet" }
 
-    fn vertex(&mut self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
         let rot = rotation_y(u.time * u.planet.rotation_speed * 1.2);
-        let model = rot * u.model;
+        let model = u.model * rot;
 
         let clip = u.proj * u.view * model * Vec4::from3(vin.pos, 1.0);
         let pos_ws = (model * Vec4::from3(vin.pos, 1.0)).xyz();
         let nrm_ws = (model * Vec4::from3(vin.nrm, 0.0)).xyz().normalize();
 
-        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv }
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, ..Default::default() }
     }
 
-    fn fragment(&mut self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Color {
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
         let view_dir = (u.camera_pos - vary.pos_ws).normalize();
         let col = self.four_layer_gradient(vary.nrm_ws, u);
 
 
-        let diff = lambert(vary.nrm_ws, u.light_dir);
-        let lit = col * (0.45 + 0.55 * diff);
+        let diff = lambert(vary.pos_ws, vary.nrm_ws, u);
+        let lit = col.hadamard(Vec3::from_scalar(0.45) + diff * 0.55);
         let rim_k = rim(vary.nrm_ws, view_dir, 4.0);
         let rim_color = self.glow_color * rim_k * 0.9;
         let band_t = 0.5 + 0.5 * vary.nrm_ws.y;
-        let band_noise = fbm_3d(vary.pos_ws * 3.0 + Vec3::new(u.time*0.6, 0.0, 0.0), 3, 2.0, 0.5, self.noise_scale);
+        let band_noise = fbm_3d(object_pos(vary.pos_ws, u) * 3.0 + Vec3::new(u.time*0.6, 0.0, 0.0), 3, 2.0, 0.5, self.noise_scale);
         let band = ((band_t * 10.0 + band_noise*2.0).fract() - 0.5).abs();
         let band_emis = (1.0 - (band * 20.0).clamp(0.0,1.0)).powf(2.0) * 0.6;
         let emis = self.glow_color * band_emis;
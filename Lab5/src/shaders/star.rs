@@ -1,35 +1,83 @@
 use crate::math::{Vec3, Vec4, rotation_y};
-use crate::renderer::{buffers::Color, uniforms::Uniforms, pipeline::{Shader, VertexIn, VertexOut}};
-use crate::shaders::noise::cellular_3d;
+use crate::renderer::{uniforms::{StarParams, Uniforms}, pipeline::{Shader, VertexIn, VertexOut}};
+use crate::renderer::raster::{Interp, MAX_VARYINGS};
+use crate::shaders::noise::{cellular_3d, cellular_3d_with};
 use super::common::*;
+use super::blackbody::{blackbody, MAX_KELVIN, MIN_KELVIN, SUN_KELVIN};
+
+// Varying propio: cuánto levantó el vertex la superficie (protuberancia de flare)
+const LIFT: usize = 0;
+
+// Manchas: grupos candidatos y duración (s) del ciclo de vida de cada uno
+const SPOT_GROUPS: usize = 8;
+const SPOT_CYCLE: f32 = 40.0;
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct Star;
 
 impl Star {
-    fn gradient(&self, t: f32) -> Vec3 {
-        // Gradiente más suave con transiciones mejoradas
-        let c0 = Vec3::new(0.85, 0.25, 0.05); // Base naranja más suave
-        let c1 = Vec3::new(1.00, 0.50, 0.10); // Naranja cálido
-        let c2 = Vec3::new(1.00, 0.80, 0.30); // Amarillo dorado
-        let c3 = Vec3::new(1.00, 0.95, 0.85); // Blanco cálido suave
-        
-        if t < 0.33 { 
-            lerp3(c0, c1, smoothstep(0.0, 0.33, t)) 
-        } else if t < 0.66 { 
-            lerp3(c1, c2, smoothstep(0.33, 0.66, t)) 
-        } else { 
-            lerp3(c2, c3, smoothstep(0.66, 1.0, t)) 
-        }
+    /// Color (lineal) y luminancia de la luz que llega a los planetas: el color de cuerpo
+    /// negro de `temperature` y una luminancia que crece con ella (1 a la temperatura del Sol).
+    /// σT⁴ se comprime a √T para que el rango de enana roja a gigante azul siga siendo
+    /// usable sin tocar la exposición. Pulsa al mismo ritmo que la superficie.
+    pub fn light(params: &StarParams, t: f32) -> (Vec3, f32) {
+        let k = params.temperature.clamp(MIN_KELVIN, MAX_KELVIN);
+        let pulse = 1.0 + (t*0.4).sin()*0.1;
+        let luminance = (k / SUN_KELVIN).sqrt() * pulse;
+        (blackbody(k), luminance)
+    }
+
+    /// Color de la superficie: las celdas brillantes están más calientes que la
+    /// temperatura efectiva y los canales oscuros entre ellas, más fríos
+    fn emission_color(&self, intensity: f32, kelvin: f32) -> Vec3 {
+        linear_to_srgb3(blackbody(kelvin * (0.72 + 0.4 * intensity)))
+    }
+
+    /// Granulación: celdas de convección (`granule_cells`, 1 en el centro de la celda) con
+    /// centros brillantes y canales oscuros, en [-1, 1] por `granulation`. Evolucionan
+    /// despacio y se apagan cuando son más finas que el pixel (`width`, en radios).
+    fn granulation(&self, d: Vec3, t: f32, width: f32, params: &StarParams) -> f32 {
+        let s = params.granule_scale;
+        let c = granule_cells(d * s + Vec3::new(t*0.07, -t*0.05, t*0.06));
+        let g = smoothstep(0.3, 0.8, c) * 2.0 - 1.0;
+        g * params.granulation * band_limit(std::f32::consts::TAU * s, width)
     }
 
-    fn emission_color(&self, intensity: f32, temp_norm: f32) -> Vec3 {
-        let base = self.gradient(intensity);
-        let hot_bias = lerp3(base, Vec3::new(1.0, 0.98, 0.92), temp_norm * 0.3);
-        hot_bias
+    /// Manchas solares: grupos de dos manchas (líder y seguidora, umbra + penumbra) en
+    /// latitudes menores a `spot_latitude` que nacen, crecen y se disuelven; al terminar
+    /// su ciclo cada grupo reaparece en otro lugar. Giran con la estrella (`d` está en su
+    /// espacio propio) y las de latitud alta se atrasan un poco (rotación diferencial).
+    /// Devuelve el brillo relativo: 1 fotosfera, ~0.65 penumbra, ~0.15 umbra.
+    fn sunspots(&self, d: Vec3, t: f32, params: &StarParams) -> f32 {
+        let max_lat = params.spot_latitude.to_radians();
+        let mut b = 1.0f32;
+        for g in 0..SPOT_GROUPS {
+            let seed = g as f32 * 17.13;
+            let age = t / SPOT_CYCLE + hash11(seed);
+            let cycle = age.floor();
+            let key = seed + cycle * 101.7;
+            if hash11(key) >= params.spot_coverage { continue; }
+
+            let size = ((age - cycle) * std::f32::consts::PI).sin();
+            let lat = (hash11(key + 1.3) * 2.0 - 1.0) * max_lat;
+            let lon = hash11(key + 2.7) * std::f32::consts::TAU
+                - t * params.rot_speed * 0.2 * lat.sin().powi(2);
+            let r = (0.07 + 0.09 * hash11(key + 4.1)) * size;
+            let gap = 2.5 * r / lat.cos();
+            for (dlon, k) in [(0.0, 1.0), (-gap, 0.7)] {
+                let (sl, cl) = lat.sin_cos();
+                let (so, co) = (lon + dlon).sin_cos();
+                let ang = d.dot(Vec3::new(cl * co, sl, cl * so)).clamp(-1.0, 1.0).acos();
+                let rr = r * k;
+                let umbra = 1.0 - smoothstep(0.35 * rr, 0.5 * rr, ang);
+                let penumbra = 1.0 - smoothstep(0.8 * rr, rr, ang);
+                b = b.min(1.0 - 0.35 * penumbra - 0.5 * umbra);
+            }
+        }
+        b
     }
 
-    fn surface_intensity(&self, p_ws: Vec3, t: f32, params: &crate::renderer::uniforms::StarParams) -> f32 {
+    fn surface_intensity(&self, p_ws: Vec3, t: f32, params: &StarParams) -> f32 {
         let noise_type = params.noise_type;
         let scale = params.noise_scale;
         
@@ -52,7 +100,7 @@ impl Star {
         saturate(combo * pulsate)
     }
 
-    fn flare_term(&self, p_ws: Vec3, t: f32, params: &crate::renderer::uniforms::StarParams) -> f32 {
+    fn flare_term(&self, p_ws: Vec3, t: f32, params: &StarParams) -> f32 {
         let scale = params.noise_scale;
         let intensity = params.flare_intensity;
         
@@ -75,7 +123,7 @@ impl Star {
 impl Shader for Star {
     fn name(&self) -> &'static str { "Star" }
 
-    fn vertex(&mut self, vin: VertexIn, u: &Uniforms) -> VertexOut {
+    fn vertex(&self, vin: VertexIn, u: &Uniforms) -> VertexOut {
         let t = u.time;
         let params = &u.star;
         let self_rot = rotation_y(t * params.rot_speed);
@@ -100,38 +148,111 @@ impl Shader for Star {
         let pos_ws = (model * Vec4::from3(displaced, 1.0)).xyz();
         let nrm_ws = (model * Vec4::from3(vin.nrm, 0.0)).xyz().normalize();
 
-        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv }
+        let mut custom = [0.0; MAX_VARYINGS];
+        custom[LIFT] = flare_ridge / 0.08;
+        VertexOut { clip_pos: clip, pos_ws, nrm_ws, uv: vin.uv, custom }
     }
 
-    fn fragment(&mut self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Color {
+    fn varyings(&self) -> &'static [Interp] { &[Interp::Perspective] }
+
+    fn emitted_light(&self, u: &Uniforms) -> Option<(Vec3, f32)> {
+        Some(Self::light(&u.star, u.time))
+    }
+
+    fn fragment(&self, vary: &crate::renderer::raster::Varyings, u: &Uniforms) -> Vec4 {
         let t = u.time;
         let params = &u.star;
         let view_dir = (u.camera_pos - vary.pos_ws).normalize();
 
-        let intensity = self.surface_intensity(vary.pos_ws * 0.9, t, params);
-        let flare = self.flare_term(vary.pos_ws, t, params);
-        let base_col = self.emission_color(intensity, params.temp_norm);
+        let p_os = object_pos(vary.pos_ws, u);
+        let d = body_dir(vary.pos_ws, u, t * params.rot_speed);
+        let width = vary.footprint() / model_scale(u).max(1e-6);
+        let gran = self.granulation(d, t, width, params);
+        let intensity = saturate(self.surface_intensity(p_os * 0.9, t, params) + gran * 0.5);
+        let flare = self.flare_term(p_os, t, params);
+        let spot = self.sunspots(d, t, params);
+
+        // Oscurecimiento al limbo: el borde se ve más oscuro y más frío (se mira más
+        // arriba en la fotosfera). Se divide por el promedio sobre el disco, 1 - k/3,
+        // para que el brillo total no cambie con `limb_darkening`
+        let mu = saturate(vary.nrm_ws.normalize().dot(view_dir));
+        let k_ld = params.limb_darkening.clamp(0.0, 1.0);
+        let limb = (1.0 - k_ld * (1.0 - mu)) / (1.0 - k_ld / 3.0);
+        let kelvin = params.temperature * (0.75 + 0.25 * spot) * (1.0 - 0.1 * params.limb_darkening * (1.0 - mu));
+        let base_col = self.emission_color(intensity, kelvin);
 
-        // Emisión más balanceada
-        let emission = (intensity.powf(1.8) * 0.7 + flare * 0.9).min(2.5);
+        // Emisión más balanceada; las protuberancias del vertex brillan un poco más
+        let lift = saturate(vary.custom[LIFT]);
+        let emission = (intensity.powf(1.8) * 0.7 * spot + flare * 0.9 + lift * 0.4).min(2.5);
 
         // Difuso más presente
-        let diff = 0.3 + 0.7*lambert(vary.nrm_ws, u.light_dir);
+        let diff = Vec3::from_scalar(0.3) + lambert(vary.pos_ws, vary.nrm_ws, u)*0.7;
 
         // Glow de borde más suave
         let glow = rim(vary.nrm_ws, view_dir, 2.5) * 0.5;
-        let glow_col = Vec3::new(1.0, 0.65, 0.25) * glow;
+        // el borde, más frío que la superficie
+        let glow_col = self.emission_color(0.0, params.temperature) * glow;
 
-        // Mezcla más equilibrada
-        let mut col = base_col * diff * 0.5 + base_col * emission * 0.5 + glow_col * 0.8;
-
-        col = col.clamp01();
+        // Mezcla más equilibrada (sin recortar: la emisión > 1 llega al framebuffer HDR)
+        let col = (base_col.hadamard(diff) * (0.5 * spot) + base_col * emission * 0.5) * limb + glow_col * 0.8;
         to_color(col)
     }
 }
 
-// Helper para transiciones suaves
-fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
-    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
-    t * t * (3.0 - 2.0 * t)
-}
\ No newline at end of file
+/// Ruido celular como `cellular_3d`, pero con un punto por celda que mezcla las tres
+/// coordenadas: con el hash de `cellular_3d` los puntos de una misma fila quedan
+/// alineados y los gránulos forman una grilla
+fn granule_cells(p: Vec3) -> f32 {
+    cellular_3d_with(p, cell_point)
+}
+
+/// Punto de la celda `c`, en [0, 1)³ dentro de ella
+#[allow(clippy::excessive_precision)]
+fn cell_point(c: Vec3) -> Vec3 {
+    let h = |k: Vec3, m: f32| (c.dot(k).sin() * m).fract().abs();
+    Vec3::new(h(Vec3::new(127.1, 311.7, 74.7), 43758.5453), h(Vec3::new(269.5, 183.3, 246.1), 22578.1459), h(Vec3::new(113.5, 271.9, 124.6), 19642.3490))
+}
+
+// Hash escalar -> [0, 1)
+#[allow(clippy::excessive_precision)]
+fn hash11(n: f32) -> f32 {
+    (n.sin() * 43758.5453).fract().abs()
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sunspots_stay_in_their_latitude_band() {
+        let params = StarParams { spot_coverage: 1.0, spot_latitude: 25.0, ..StarParams::default() };
+        let mut dark = 0;
+        for t in [3.0, 17.0, 50.0] {
+            for i in 0..64 {
+                for j in 0..32 {
+                    let (lon, lat) = (i as f32 / 64.0 * std::f32::consts::TAU, (j as f32 / 31.0 - 0.5) * std::f32::consts::PI);
+                    let d = Vec3::new(lat.cos() * lon.cos(), lat.sin(), lat.cos() * lon.sin());
+                    if Star.sunspots(d, t, &params) < 1.0 {
+                        dark += 1;
+                        // Radio máximo de una mancha: 0.16 rad (~9°)
+                        assert!(lat.to_degrees().abs() < 25.0 + 10.0, "mancha en latitud {}", lat.to_degrees());
+                    }
+                }
+            }
+        }
+        assert!(dark > 0);
+        let clean = StarParams { spot_coverage: 0.0, ..params };
+        assert_eq!(Star.sunspots(Vec3::new(1.0, 0.0, 0.0), 3.0, &clean), 1.0);
+    }
+
+    #[test]
+    fn granule_cells_are_not_grid_aligned() {
+        // A lo largo de una columna de celdas, los puntos no comparten la coordenada x
+        let xs: Vec<f32> = (0..8).map(|j| cell_point(Vec3::new(0.0, j as f32, 0.0)).x).collect();
+        let spread = xs.iter().cloned().fold(f32::MIN, f32::max) - xs.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(spread > 0.3, "{:?}", xs);
+        let c = granule_cells(Vec3::new(0.3, 0.7, 0.2));
+        assert!((0.0..=1.0).contains(&c));
+    }
+}
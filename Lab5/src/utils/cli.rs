@@ -0,0 +1,162 @@
+use crate::renderer::{Bloom, Msaa, Tonemap, ToneMapping};
+use crate::shaders::ShaderKind;
+
+/// Opciones de línea de comandos (viewer y modo headless)
+#[derive(Clone, Debug)]
+pub struct CliArgs {
+    pub headless: bool,
+    pub width: u32,
+    pub height: u32,
+    pub time: f32,        // tiempo inicial (segundos)
+    pub dt: f32,          // paso de tiempo entre frames
+    pub frames: u32,      // cantidad de frames a renderizar
+    pub shader: Option<ShaderKind>, // None = sistema solar completo
+    pub scene: Option<String>,      // archivo de escena TOML
+    pub tonemap: ToneMapping,
+    pub bloom: f32,       // intensidad del bloom (0 = apagado)
+    pub msaa: Msaa,       // muestras por pixel (se fija al arrancar)
+    pub shadow_map: usize, // lado del mapa de sombras en texels (0 = sin sombras)
+    pub output: String,   // ruta del PNG de salida
+    pub mesh: String,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            width: 960,
+            height: 540,
+            time: 0.0,
+            dt: 1.0 / 30.0,
+            frames: 1,
+            shader: None,
+            scene: None,
+            tonemap: ToneMapping::default(),
+            bloom: Bloom::default().strength,
+            msaa: Msaa::Off,
+            shadow_map: 512,
+            output: "renders/frame.png".to_string(),
+            mesh: "assets/sphere.obj".to_string(),
+        }
+    }
+}
+
+impl CliArgs {
+    /// Parsea los argumentos (sin el nombre del programa).
+    /// Devuelve `Ok(None)` si se pidió `--help`.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut out = Self::default();
+        let mut it = args.into_iter();
+
+        while let Some(arg) = it.next() {
+            let mut value = |name: &str| it.next().ok_or_else(|| format!("Falta valor para {}", name));
+            match arg.as_str() {
+                "--headless" => out.headless = true,
+                "--width" => out.width = parse_num(&arg, &value(&arg)?)?,
+                "--height" => out.height = parse_num(&arg, &value(&arg)?)?,
+                "--time" | "-t" => out.time = parse_num(&arg, &value(&arg)?)?,
+                "--dt" => out.dt = parse_num(&arg, &value(&arg)?)?,
+                "--frames" | "-n" => out.frames = parse_num(&arg, &value(&arg)?)?,
+                "--out" | "-o" => out.output = value(&arg)?,
+                "--mesh" => out.mesh = value(&arg)?,
+                "--shader" | "-s" => {
+                    let v = value(&arg)?;
+                    out.shader = Some(ShaderKind::from_name(&v)
+                        .ok_or_else(|| format!("Shader desconocido '{}' (opciones: {})", v, shader_names()))?);
+                }
+                "--scene" => out.scene = Some(value(&arg)?),
+                "--tonemap" => {
+                    let v = value(&arg)?;
+                    out.tonemap.operator = Tonemap::from_name(&v)
+                        .ok_or_else(|| format!("Tonemap desconocido '{}' (opciones: exposure, reinhard, aces)", v))?;
+                }
+                "--exposure" => out.tonemap.exposure = parse_num(&arg, &value(&arg)?)?,
+                "--bloom" => out.bloom = parse_num(&arg, &value(&arg)?)?,
+                "--msaa" => {
+                    let n = parse_num(&arg, &value(&arg)?)?;
+                    out.msaa = Msaa::from_samples(n)
+                        .ok_or_else(|| format!("MSAA inválido '{}' (opciones: 1, 2, 4, 8)", n))?;
+                }
+                "--shadow-map" => out.shadow_map = parse_num(&arg, &value(&arg)?)?,
+                "--help" => return Ok(None),
+                _ => return Err(format!("Argumento desconocido '{}' (usa --help)", arg)),
+            }
+        }
+
+        if out.width == 0 || out.height == 0 {
+            return Err("El tamaño debe ser mayor que 0".to_string());
+        }
+        if out.scene.is_some() && out.shader.is_some() {
+            return Err("Usa --scene o --shader, no ambos".to_string());
+        }
+        if out.frames == 0 {
+            return Err("--frames debe ser al menos 1".to_string());
+        }
+        Ok(Some(out))
+    }
+
+    /// Ruta de salida del frame `i`: si hay más de un frame se agrega `_0000` antes de la extensión
+    pub fn frame_path(&self, i: u32) -> String {
+        if self.frames <= 1 {
+            return self.output.clone();
+        }
+        match self.output.rfind('.') {
+            Some(dot) if !self.output[dot..].contains('/') => {
+                format!("{}_{:04}{}", &self.output[..dot], i, &self.output[dot..])
+            }
+            _ => format!("{}_{:04}.png", self.output, i),
+        }
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(name: &str, v: &str) -> Result<T, String> {
+    v.parse().map_err(|_| format!("Valor inválido para {}: '{}'", name, v))
+}
+
+fn shader_names() -> String {
+    ShaderKind::ALL.iter().map(|k| k.name()).collect::<Vec<_>>().join(", ")
+}
+
+pub fn print_usage() {
+    println!("Uso: Lab5 [opciones]");
+    println!("  --headless          Renderiza sin ventana y guarda PNGs");
+    println!("  --width <px>        Ancho (default 960)");
+    println!("  --height <px>       Alto (default 540)");
+    println!("  --time, -t <s>      Tiempo inicial en segundos (default 0)");
+    println!("  --dt <s>            Paso de tiempo entre frames (default 1/30)");
+    println!("  --frames, -n <n>    Cantidad de frames (default 1)");
+    println!("  --shader, -s <k>    Un solo cuerpo centrado: {} (default: sistema solar)", shader_names());
+    println!("  --scene <ruta>      Escena TOML (cámara, luz, cuerpos; ver scenes/)");
+    println!("  --tonemap <op>      Tonemapping: exposure, reinhard, aces (default exposure)");
+    println!("  --exposure <ev>     Exposición en stops (default 0)");
+    println!("  --bloom <k>         Intensidad del bloom, 0 lo apaga (default {:.1})", Bloom::default().strength);
+    println!("  --msaa <n>          Muestras por pixel: 1, 2, 4, 8 (default 1)");
+    println!("  --shadow-map <px>   Lado del mapa de sombras, 0 las apaga (default 512)");
+    println!("  --out, -o <ruta>    PNG de salida (default renders/frame.png)");
+    println!("  --mesh <ruta>       Malla .obj (default assets/sphere.obj)");
+    println!("  --help              Mostrar esta ayuda");
+}
+
+// Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> { s.split_whitespace().map(String::from).collect() }
+
+    #[test]
+    fn parse_headless_args() {
+        let a = CliArgs::parse(args("--headless --width 320 --height 200 --time 1.5 --shader gas -n 3 -o out/x.png"))
+            .unwrap().unwrap();
+        assert!(a.headless);
+        assert_eq!((a.width, a.height, a.frames), (320, 200, 3));
+        assert_eq!(a.shader, Some(ShaderKind::Gas));
+        assert_eq!(a.frame_path(2), "out/x_0002.png");
+        assert!(CliArgs::parse(args("--shader nope")).is_err());
+        assert!(CliArgs::parse(args("--help")).unwrap().is_none());
+        assert!(CliArgs::parse(args("--scene a.toml --shader star")).is_err());
+        assert_eq!(CliArgs::parse(args("--msaa 4")).unwrap().unwrap().msaa, Msaa::X4);
+        assert!(CliArgs::parse(args("--msaa 3")).is_err());
+        assert_eq!(CliArgs::parse(args("--shadow-map 0")).unwrap().unwrap().shadow_map, 0);
+    }
+}
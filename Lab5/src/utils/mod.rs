@@ -1 +1,4 @@
+//! Utilidades: argumentos de línea de comandos y contador de FPS.
+
 pub mod timer;
+pub mod cli;